@@ -6,6 +6,7 @@ use anyhow::{ensure, Context, Result};
 use diem_crypto::HashValue;
 use diem_framework::{BuildOptions, BuiltPackage, ReleasePackage};
 use diem_types::account_address::AccountAddress;
+use libra_types::core_types::proposal_metadata::{ProposalMetadata, ScriptStep};
 use std::path::{Path, PathBuf};
 
 /// Core modules address to deploy to
@@ -145,6 +146,65 @@ pub fn write_to_file(result: Vec<(String, String)>, proposal_dir: PathBuf) -> an
     }
     Ok(())
 }
+
+/// Write the metadata document of the upgrade next to the script dirs, with
+/// the hash of each step from its script_sha3 file. The title, description,
+/// release name and changelog are taken from the template.
+pub fn write_proposal_metadata(
+    proposal_dir: &Path,
+    framework_local_dir: &Path,
+    template: ProposalMetadata,
+) -> Result<PathBuf> {
+    let meta = ProposalMetadata {
+        framework_git_hash: get_framework_git_head(framework_local_dir)
+            .unwrap_or("none".to_owned()),
+        steps: read_script_steps(proposal_dir)?,
+        ..template
+    };
+    let path = meta.save(proposal_dir)?;
+    println!("proposal metadata saved to {}", path.display());
+    println!("publish this file, and use its url with `libra txs governance propose`");
+    Ok(path)
+}
+
+/// The compiled scripts of an upgrade, in deploy order, with the hash from
+/// their script_sha3 file.
+pub fn read_script_steps(proposal_dir: &Path) -> Result<Vec<ScriptStep>> {
+    // the script dirs are named by their deploy order, e.g. 1-move-stdlib
+    let mut steps = vec![];
+    for entry in std::fs::read_dir(proposal_dir)? {
+        let dir = entry?.path();
+        let hash_path = dir.join("script_sha3");
+        if !hash_path.exists() {
+            continue;
+        }
+        let name = dir
+            .file_name()
+            .context("no dir name")?
+            .to_string_lossy()
+            .to_string();
+        let order: usize = name
+            .split('-')
+            .next()
+            .and_then(|n| n.parse().ok())
+            .context(format!("script dir {} does not start with its order", name))?;
+        steps.push((
+            order,
+            ScriptStep {
+                name,
+                script_hash: std::fs::read_to_string(hash_path)?.trim().to_owned(),
+            },
+        ));
+    }
+    ensure!(
+        !steps.is_empty(),
+        "no compiled scripts found in {}",
+        proposal_dir.display()
+    );
+    steps.sort_by_key(|(order, _)| *order);
+    Ok(steps.into_iter().map(|(_, s)| s).collect())
+}
+
 /// Need to create a dummy package so that we can build the script into bytecode
 /// so that we can then get the hash of the script.
 /// ... so that we can then submit it as part of a proposal framework/libra-framework/sources/modified_source/diem_governance.move
@@ -224,8 +284,8 @@ pub fn save_build(
     Ok(())
 }
 
-fn get_framework_git_head(path: &Path) -> anyhow::Result<String> {
-    let r = git2::Repository::discover(path).unwrap();
+pub fn get_framework_git_head(path: &Path) -> anyhow::Result<String> {
+    let r = git2::Repository::discover(path)?;
     let id = r.head()?.peel_to_commit()?.id();
 
     Ok(id.to_string())
@@ -0,0 +1,174 @@
+//! verify a framework upgrade proposal by rebuilding it from source
+//! The governance scripts of an upgrade are chained: each script includes the
+//! execution hash of the next step, and only the hash of the step to execute
+//! next is in the proposal. So rebuilding all the steps from the same commit,
+//! with the same build options, must give that hash at one of the steps.
+
+use crate::builder::framework_generate_upgrade_proposal::{
+    get_framework_git_head, make_framework_upgrade_artifacts, read_script_steps,
+};
+use anyhow::{bail, ensure, Context, Result};
+use diem_temppath::TempPath;
+use libra_types::core_types::proposal_metadata::ScriptStep;
+use std::path::Path;
+
+/// The framework source must be a clean checkout of the commit to verify,
+/// since the commit is also written into the upgrade scripts.
+pub fn check_framework_checkout(framework_local_dir: &Path, git_ref: &str) -> Result<String> {
+    let repo = git2::Repository::discover(framework_local_dir).context(format!(
+        "framework source at {} is not in a git repo",
+        framework_local_dir.display()
+    ))?;
+    let wanted = repo
+        .revparse_single(git_ref)
+        .context(format!("cannot find git ref {}", git_ref))?
+        .peel_to_commit()?
+        .id()
+        .to_string();
+    let head = get_framework_git_head(framework_local_dir)?;
+    ensure!(
+        head == wanted,
+        "the framework checkout is at commit {}, not {}. Checkout {} first",
+        head,
+        wanted,
+        git_ref
+    );
+
+    // only changes to the framework source change the build
+    let workdir = repo
+        .workdir()
+        .context("framework repo has no working dir")?
+        .canonicalize()?;
+    let source_dir = framework_local_dir.canonicalize()?;
+    let mut opts = git2::StatusOptions::new();
+    opts.include_untracked(true).include_ignored(false);
+    if let Ok(rel) = source_dir.strip_prefix(&workdir) {
+        if !rel.as_os_str().is_empty() {
+            opts.pathspec(rel);
+        }
+    }
+    let changed = repo.statuses(Some(&mut opts))?.len();
+    ensure!(
+        changed == 0,
+        "the framework checkout has {} uncommitted changes, so the build would not be of {}",
+        changed,
+        git_ref
+    );
+    Ok(head)
+}
+
+/// Build all the steps of the upgrade in a temp dir, and return their
+/// execution hashes in deploy order.
+pub fn rebuild_upgrade_steps(
+    framework_local_dir: &Path,
+    core_modules: &Option<Vec<String>>,
+    force_incompatible_upgrade: bool,
+) -> Result<Vec<ScriptStep>> {
+    let temp = TempPath::new();
+    temp.create_as_dir()?;
+    make_framework_upgrade_artifacts(
+        temp.path(),
+        framework_local_dir,
+        core_modules,
+        force_incompatible_upgrade,
+    )?;
+    read_script_steps(temp.path())
+}
+
+/// Find the rebuilt step which the proposal executes next, by its hash.
+/// Resolving a step replaces the execution hash of the proposal with the hash
+/// of the next step, so the chain can be at any of the steps.
+/// When the steps of the published proposal metadata are known, they are
+/// used to tell which step differs.
+pub fn compare_upgrade_steps(
+    local: &[ScriptStep],
+    execution_hash: &str,
+    published: Option<&[ScriptStep]>,
+) -> Result<usize> {
+    ensure!(!local.is_empty(), "no upgrade steps were built");
+    let onchain = execution_hash.trim().trim_start_matches("0x");
+
+    // a step which differs, also changes the hashes of all the steps before it.
+    // So the last differing step is where the source differs.
+    let differs = published.and_then(|p| {
+        local.iter().rev().find_map(|step| {
+            let other = p.iter().find(|s| s.name == step.name);
+            match other {
+                Some(s) if s.script_hash.eq_ignore_ascii_case(&step.script_hash) => None,
+                _ => Some((step, other)),
+            }
+        })
+    });
+
+    if let Some(i) = local
+        .iter()
+        .position(|s| s.script_hash.eq_ignore_ascii_case(onchain))
+    {
+        if let Some((step, _)) = differs {
+            println!(
+                "WARN: the proposal metadata does not list the hash of step {}",
+                step.name
+            );
+        }
+        return Ok(i);
+    }
+
+    match differs {
+        Some((step, Some(other))) => bail!(
+            "step {} differs: rebuilt {}, published {}",
+            step.name,
+            step.script_hash,
+            other.script_hash
+        ),
+        Some((step, None)) => bail!(
+            "step {} is not in the published proposal metadata",
+            step.name
+        ),
+        None if published.is_some() => bail!(
+            "all the steps match the published metadata, but none of them rebuilds to {}, which the proposal executes. The metadata does not describe this proposal",
+            onchain
+        ),
+        None => bail!(
+            "none of the {} rebuilt steps has the hash {}, which the proposal executes. Without the proposal metadata the differing step cannot be found",
+            local.len(),
+            onchain
+        ),
+    }
+}
+
+#[test]
+fn compare_chained_steps() {
+    let step = |name: &str, hash: &str| ScriptStep {
+        name: name.to_owned(),
+        script_hash: hash.to_owned(),
+    };
+    let local = vec![
+        step("1-move-stdlib", "aa"),
+        step("2-vendor-stdlib", "bb"),
+        step("3-libra-framework", "cc"),
+    ];
+
+    assert_eq!(compare_upgrade_steps(&local, "0xAA", None).unwrap(), 0);
+    assert!(compare_upgrade_steps(&local, "0xab", None).is_err());
+
+    // the first steps were resolved, so the proposal executes a later one
+    assert_eq!(compare_upgrade_steps(&local, "0xbb", None).unwrap(), 1);
+    assert_eq!(compare_upgrade_steps(&local, "cc", None).unwrap(), 2);
+
+    // the framework changed, so the hashes of the earlier steps changed too
+    let published = vec![
+        step("1-move-stdlib", "a1"),
+        step("2-vendor-stdlib", "b1"),
+        step("3-libra-framework", "c1"),
+    ];
+    let err = compare_upgrade_steps(&local, "0xa1", Some(&published)).unwrap_err();
+    assert!(err.to_string().contains("3-libra-framework"));
+
+    let published = vec![
+        step("1-move-stdlib", "a1"),
+        step("2-vendor-stdlib", "b1"),
+        step("3-libra-framework", "cc"),
+    ];
+    let err = compare_upgrade_steps(&local, "0xa1", Some(&published)).unwrap_err();
+    assert!(err.to_string().contains("2-vendor-stdlib"));
+}
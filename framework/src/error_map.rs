@@ -0,0 +1,59 @@
+//! The error maps of the framework modules, read from the release bundles
+//! in framework/releases, which are embedded in the binary.
+
+use anyhow::Context;
+use diem_framework::ReleaseBundle;
+use diem_types::vm::module_metadata::get_metadata_from_compiled_module;
+use libra_types::core_types::error_map::ErrorMaps;
+use once_cell::sync::OnceCell;
+
+static HEAD_MRB: &[u8] = include_bytes!("../releases/head.mrb");
+static MAINNET_MRB: &[u8] = include_bytes!("../releases/mainnet.mrb");
+
+static FRAMEWORK_ERRORS: OnceCell<ErrorMaps> = OnceCell::new();
+
+/// The error maps of the head and mainnet releases. The mainnet release wins
+/// where it differs from head, since that is what most transactions run against.
+pub fn framework_error_maps() -> anyhow::Result<&'static ErrorMaps> {
+    FRAMEWORK_ERRORS.get_or_try_init(|| {
+        let mut maps = ErrorMaps::new();
+        for (name, bytes) in [("head", HEAD_MRB), ("mainnet", MAINNET_MRB)] {
+            let bundle = bcs::from_bytes::<ReleaseBundle>(bytes).context(format!(
+                "could not read the embedded {} release bundle",
+                name
+            ))?;
+            maps.extend(error_maps(&bundle));
+        }
+        Ok(maps)
+    })
+}
+
+/// Collect the error maps of every module in a release bundle.
+pub fn error_maps(bundle: &ReleaseBundle) -> ErrorMaps {
+    bundle
+        .compiled_modules()
+        .iter()
+        .filter_map(|m| {
+            let metadata = get_metadata_from_compiled_module(m)?;
+            if metadata.error_map.is_empty() {
+                return None;
+            }
+            Some((m.self_id().short_str_lossless(), metadata.error_map))
+        })
+        .collect()
+}
+
+#[test]
+fn explain_framework_aborts() {
+    use libra_types::core_types::error_map::{explain_abort, explain_vm_status};
+
+    let maps = framework_error_maps().unwrap();
+    // slow_wallet::EGENESIS_ERROR, with error::invalid_argument
+    let e = explain_abort(maps, "0x1::slow_wallet", 0x10001).expect("no error map for slow_wallet");
+    assert_eq!(e.reason_name, "EGENESIS_ERROR");
+    assert_eq!(e.category.as_deref(), Some("INVALID_ARGUMENT"));
+    assert!(e.description.contains("slow wallet registry"));
+
+    let from_api = explain_vm_status(maps, "Move abort in 0x1::slow_wallet: 0x10001");
+    assert_eq!(from_api, Some(e));
+}
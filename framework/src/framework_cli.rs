@@ -3,12 +3,18 @@
 use crate::{
     builder::framework_generate_upgrade_proposal::{
         init_move_dir_wrapper, libra_compile_script, make_framework_upgrade_artifacts, save_build,
+        write_proposal_metadata,
+    },
+    builder::framework_verify_upgrade::{
+        check_framework_checkout, compare_upgrade_steps, rebuild_upgrade_steps,
     },
     release::ReleaseTarget,
+    release_diff::diff_bundles,
 };
 
-use anyhow::Context;
+use anyhow::{bail, Context};
 use clap::Parser;
+use libra_types::core_types::proposal_metadata::ProposalMetadata;
 use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
@@ -21,6 +27,10 @@ pub enum FrameworkCli {
     Governance(GovernanceScript),
     /// Creates all artifacts for a network governance upgrade
     Upgrade(FrameworkUpgrade),
+    /// Lists the module ABI changes between two .mrb release bundles
+    Diff(BundleDiff),
+    /// Rebuilds an upgrade proposal from source, and checks it is the one on chain
+    VerifyUpgrade(VerifyUpgrade),
 }
 
 impl FrameworkCli {
@@ -29,6 +39,8 @@ impl FrameworkCli {
             FrameworkCli::Release(tool) => tool.execute(),
             FrameworkCli::Governance(tool) => tool.execute(),
             FrameworkCli::Upgrade(tool) => tool.execute(),
+            FrameworkCli::Diff(tool) => tool.execute(),
+            FrameworkCli::VerifyUpgrade(tool) => tool.execute(),
         }
     }
 }
@@ -74,6 +86,22 @@ pub struct FrameworkUpgrade {
     /// I hope to god you know what you are doing.
     #[clap(long)]
     pub danger_force_upgrade: bool,
+
+    /// optional, name of the release for the proposal metadata. Defaults to the name of the output dir
+    #[clap(long)]
+    pub release_name: Option<String>,
+
+    /// optional, title of the proposal metadata
+    #[clap(long)]
+    pub title: Option<String>,
+
+    /// optional, description of the upgrade for the voters
+    #[clap(long)]
+    pub description: Option<String>,
+
+    /// optional, url of the changelog of the release
+    #[clap(long)]
+    pub changelog_url: Option<String>,
 }
 
 impl FrameworkUpgrade {
@@ -94,6 +122,186 @@ impl FrameworkUpgrade {
             self.danger_force_upgrade,
         )?;
 
+        let release_name = self.release_name.clone().unwrap_or_else(|| {
+            self.output_dir
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default()
+        });
+        write_proposal_metadata(
+            &self.output_dir,
+            &self.framework_local_dir,
+            ProposalMetadata {
+                title: self
+                    .title
+                    .clone()
+                    .unwrap_or_else(|| format!("Framework upgrade {}", release_name)),
+                description: self.description.clone().unwrap_or_default(),
+                release_name,
+                changelog_url: self.changelog_url.clone(),
+                ..Default::default()
+            },
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Rebuilds all the steps of a framework upgrade from a checkout of the
+/// framework source, and compares the execution hash with the proposal on chain.
+/// The `libra` cli reads the proposal from the chain, this library only compares.
+#[derive(Debug, Parser)]
+pub struct VerifyUpgrade {
+    /// On-chain ID of the proposal
+    #[clap(short = 'i', long)]
+    pub proposal_id: u64,
+
+    /// git commit, tag or branch the proposal was built from. The framework checkout must be at this commit
+    #[clap(long)]
+    pub git_ref: String,
+
+    /// directory of the framework source code. Usually `./framework`
+    #[clap(short, long)]
+    pub framework_local_dir: PathBuf,
+
+    /// optional, list of core module directory names the proposal was built with
+    #[clap(long)]
+    pub core_modules: Option<Vec<String>>,
+
+    /// optional, if the proposal was built with --danger-force-upgrade
+    #[clap(long)]
+    pub danger_force_upgrade: bool,
+
+    /// optional, execution hash of the proposal. `libra move framework verify-upgrade` reads it from the chain
+    #[clap(long)]
+    pub execution_hash: Option<String>,
+
+    /// optional, path to the proposal metadata document, to tell which step differs
+    #[clap(long)]
+    pub metadata_file: Option<PathBuf>,
+
+    /// optional, URL of the node to read the proposal from. Otherwise will default to what is in the config file
+    #[clap(short, long)]
+    pub url: Option<String>,
+
+    /// optional, path to the libra cli config file
+    #[clap(short, long)]
+    pub config_path: Option<PathBuf>,
+}
+
+impl VerifyUpgrade {
+    /// compare with the --execution-hash and --metadata-file given
+    pub fn execute(&self) -> anyhow::Result<()> {
+        let execution_hash = self.execution_hash.as_deref().context(
+            "the execution hash of the proposal is needed. Pass --execution-hash, or use `libra move framework verify-upgrade` to read it from the chain",
+        )?;
+        let published = match &self.metadata_file {
+            Some(p) => Some(ProposalMetadata::read(p)?.0),
+            None => None,
+        };
+        self.verify(execution_hash, false, published.as_ref())
+    }
+
+    /// rebuild the steps, and compare them with the execution hash of the proposal
+    pub fn verify(
+        &self,
+        execution_hash: &str,
+        is_resolved: bool,
+        published: Option<&ProposalMetadata>,
+    ) -> anyhow::Result<()> {
+        // the hash of a resolved proposal is of its last step, which already ran
+        if is_resolved {
+            bail!(
+                "proposal {} is already resolved, all of its steps were executed. There is no pending step to verify",
+                self.proposal_id
+            );
+        }
+
+        let commit = check_framework_checkout(&self.framework_local_dir, &self.git_ref)?;
+        if let Some(meta) = published {
+            if meta.framework_git_hash != commit {
+                println!(
+                    "WARN: the proposal metadata says it was built from commit {}",
+                    meta.framework_git_hash
+                );
+            }
+        }
+
+        let steps = rebuild_upgrade_steps(
+            &self.framework_local_dir,
+            &self.core_modules,
+            self.danger_force_upgrade,
+        )?;
+        for s in &steps {
+            println!("step {}: script hash {}", s.name, s.script_hash);
+        }
+
+        let at = compare_upgrade_steps(
+            &steps,
+            execution_hash,
+            published.map(|m| m.steps.as_slice()),
+        )
+        .context(format!(
+            "MISMATCH: proposal {} was not built from {}",
+            self.proposal_id, self.git_ref
+        ))?;
+        println!(
+            "the proposal executes step {} of {}: {}",
+            at + 1,
+            steps.len(),
+            steps[at].name
+        );
+        if at > 0 {
+            println!("the steps before it were already resolved");
+        }
+
+        println!(
+            "VERIFIED: proposal {} is the upgrade built from {} at commit {}",
+            self.proposal_id, self.git_ref, commit
+        );
+        Ok(())
+    }
+}
+
+/// Compares the modules of two release bundles, e.g. from `framework/releases`.
+/// Changes which the compatible upgrade policy would reject are flagged as breaking.
+#[derive(Debug, Parser)]
+pub struct BundleDiff {
+    /// path to the .mrb file of the old release
+    pub old: PathBuf,
+
+    /// path to the .mrb file of the new release
+    pub new: PathBuf,
+
+    /// optional, only list the breaking changes
+    #[clap(long)]
+    pub breaking_only: bool,
+
+    /// optional, exit with an error if there are breaking changes
+    #[clap(long)]
+    pub fail_on_breaking: bool,
+}
+
+impl BundleDiff {
+    pub fn execute(&self) -> anyhow::Result<()> {
+        let old = ReleaseTarget::load_bundle_from_file(self.old.clone())
+            .context(format!("cannot read release bundle {}", self.old.display()))?;
+        let new = ReleaseTarget::load_bundle_from_file(self.new.clone())
+            .context(format!("cannot read release bundle {}", self.new.display()))?;
+
+        let changes = diff_bundles(&old, &new);
+        let breaking = changes.iter().filter(|c| c.breaking).count();
+        for c in changes.iter().filter(|c| c.breaking || !self.breaking_only) {
+            println!("{}", c);
+        }
+        println!("{} changes, {} breaking", changes.len(), breaking);
+
+        if breaking > 0 {
+            println!("the compatible upgrade policy would reject this release, it can only be deployed with --danger-force-upgrade");
+            if self.fail_on_breaking {
+                bail!("{} breaking changes", breaking);
+            }
+        }
         Ok(())
     }
 }
@@ -1,6 +1,8 @@
 pub mod builder;
+pub mod error_map;
 pub mod framework_cli;
 pub mod release;
+pub mod release_diff;
 pub mod upgrade_fixtures;
 
 //////// 0L ///////
@@ -10,5 +10,7 @@ fn main() -> anyhow::Result<()> {
         FrameworkCli::Release(release) => release.execute(),
         FrameworkCli::Upgrade(release) => release.execute(),
         FrameworkCli::Governance(release) => release.execute(),
+        FrameworkCli::Diff(diff) => diff.execute(),
+        FrameworkCli::VerifyUpgrade(verify) => verify.execute(),
     }
 }
@@ -0,0 +1,250 @@
+//! Compare the module ABIs of two release bundles.
+//! Lists what was added, removed or changed in the modules, and flags the
+//! changes which the compatible upgrade policy would reject, e.g. removing a
+//! public function or changing the layout of a struct.
+
+use diem_framework::ReleaseBundle;
+use move_binary_format::{
+    compatibility::Compatibility,
+    file_format::{AbilitySet, Visibility},
+    normalized::{Function, Module, Struct},
+    CompiledModule,
+};
+use std::{collections::BTreeMap, fmt::Display};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// One difference between the modules of two releases
+#[derive(Debug, Clone)]
+pub struct ApiChange {
+    /// e.g. 0x1::coin
+    pub module: String,
+    /// e.g. `public function transfer`, or `module` for the module itself
+    pub item: String,
+    pub kind: ChangeKind,
+    pub detail: String,
+    /// the compatible upgrade policy would reject this change
+    pub breaking: bool,
+}
+
+impl Display for ApiChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = match self.kind {
+            ChangeKind::Added => "+",
+            ChangeKind::Removed => "-",
+            ChangeKind::Changed => "~",
+        };
+        write!(f, "{} {} {}", sign, self.module, self.item)?;
+        if !self.detail.is_empty() {
+            write!(f, ": {}", self.detail)?;
+        }
+        if self.breaking {
+            write!(f, " [BREAKING]")?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare all the modules of two release bundles.
+pub fn diff_bundles(old: &ReleaseBundle, new: &ReleaseBundle) -> Vec<ApiChange> {
+    diff_modules(&old.compiled_modules(), &new.compiled_modules())
+}
+
+/// Compare two sets of modules by their id.
+pub fn diff_modules(old: &[CompiledModule], new: &[CompiledModule]) -> Vec<ApiChange> {
+    let by_id = |list: &[CompiledModule]| -> BTreeMap<String, Module> {
+        list.iter()
+            .map(|m| (m.self_id().short_str_lossless(), Module::new(m)))
+            .collect()
+    };
+    let old = by_id(old);
+    let new = by_id(new);
+
+    let mut changes = vec![];
+    for (id, old_mod) in &old {
+        match new.get(id) {
+            Some(new_mod) => changes.extend(diff_module(id, old_mod, new_mod)),
+            None => changes.push(change(id, "module", ChangeKind::Removed, "", true)),
+        }
+    }
+    for id in new.keys().filter(|id| !old.contains_key(*id)) {
+        changes.push(change(id, "module", ChangeKind::Added, "", false));
+    }
+    changes
+}
+
+fn change(module: &str, item: &str, kind: ChangeKind, detail: &str, breaking: bool) -> ApiChange {
+    ApiChange {
+        module: module.to_owned(),
+        item: item.to_owned(),
+        kind,
+        detail: detail.to_owned(),
+        breaking,
+    }
+}
+
+fn diff_module(id: &str, old: &Module, new: &Module) -> Vec<ApiChange> {
+    let mut changes = vec![];
+
+    for (name, old_s) in &old.structs {
+        let item = format!("struct {}", name);
+        match new.structs.get(name) {
+            Some(new_s) if new_s == old_s => {}
+            Some(new_s) => {
+                let (detail, breaking) = struct_change(old_s, new_s);
+                changes.push(change(id, &item, ChangeKind::Changed, &detail, breaking));
+            }
+            None => changes.push(change(id, &item, ChangeKind::Removed, "", true)),
+        }
+    }
+    for (name, s) in new
+        .structs
+        .iter()
+        .filter(|(n, _)| !old.structs.contains_key(*n))
+    {
+        let detail = format!("abilities {}", abilities(s.abilities));
+        changes.push(change(
+            id,
+            &format!("struct {}", name),
+            ChangeKind::Added,
+            &detail,
+            false,
+        ));
+    }
+
+    for (name, old_f) in &old.exposed_functions {
+        let item = format!("{} {}", function_kind(old_f), name);
+        // friend functions can only be called by the modules of the same package
+        let is_api = old_f.visibility == Visibility::Public || old_f.is_entry;
+        match new.exposed_functions.get(name) {
+            Some(new_f) if new_f == old_f => {}
+            Some(new_f) => {
+                let (detail, breaking) = function_change(old_f, new_f);
+                changes.push(change(
+                    id,
+                    &item,
+                    ChangeKind::Changed,
+                    &detail,
+                    breaking && is_api,
+                ));
+            }
+            None => changes.push(change(id, &item, ChangeKind::Removed, "", is_api)),
+        }
+    }
+    for (name, f) in new
+        .exposed_functions
+        .iter()
+        .filter(|(n, _)| !old.exposed_functions.contains_key(*n))
+    {
+        let item = format!("{} {}", function_kind(f), name);
+        changes.push(change(id, &item, ChangeKind::Added, "", false));
+    }
+
+    for friend in old.friends.iter().filter(|f| !new.friends.contains(f)) {
+        let item = format!("friend {}", friend.short_str_lossless());
+        changes.push(change(id, &item, ChangeKind::Removed, "", false));
+    }
+    for friend in new.friends.iter().filter(|f| !old.friends.contains(f)) {
+        let item = format!("friend {}", friend.short_str_lossless());
+        changes.push(change(id, &item, ChangeKind::Added, "", false));
+    }
+
+    // the check of the VM is the reference, in case a rule above misses a case
+    if let Err(e) = Compatibility::new(true, true, false).check(old, new) {
+        if !changes.iter().any(|c| c.breaking) {
+            let detail = format!("incompatible upgrade, {:?}", e.major_status());
+            changes.push(change(id, "module", ChangeKind::Changed, &detail, true));
+        }
+    }
+    changes
+}
+
+fn function_kind(f: &Function) -> &'static str {
+    match (f.is_entry, f.visibility) {
+        (true, Visibility::Public) => "public entry function",
+        (true, _) => "entry function",
+        (false, Visibility::Public) => "public function",
+        (false, _) => "friend function",
+    }
+}
+
+fn abilities(set: AbilitySet) -> String {
+    let list: Vec<String> = set.into_iter().map(|a| format!("{:?}", a)).collect();
+    format!("[{}]", list.join(", "))
+}
+
+/// the description of a struct change, and if it is breaking
+fn struct_change(old: &Struct, new: &Struct) -> (String, bool) {
+    let mut detail = vec![];
+    let mut breaking = false;
+    if old.abilities != new.abilities {
+        detail.push(format!(
+            "abilities {} -> {}",
+            abilities(old.abilities),
+            abilities(new.abilities)
+        ));
+        // abilities can be added, but not removed
+        breaking |= !old.abilities.is_subset(new.abilities);
+    }
+    if old.type_parameters != new.type_parameters {
+        detail.push("type parameters changed".to_owned());
+        breaking = true;
+    }
+    if old.fields != new.fields {
+        // the layout of stored resources cannot change
+        detail.push("fields changed".to_owned());
+        breaking = true;
+    }
+    (detail.join(", "), breaking)
+}
+
+/// the description of a function change, and if it is breaking for callers
+fn function_change(old: &Function, new: &Function) -> (String, bool) {
+    let mut detail = vec![];
+    let mut breaking = false;
+    if old.visibility != new.visibility {
+        detail.push(format!(
+            "visibility {:?} -> {:?}",
+            old.visibility, new.visibility
+        ));
+        breaking |= old.visibility == Visibility::Public;
+    }
+    if old.is_entry != new.is_entry {
+        detail.push(format!("entry {} -> {}", old.is_entry, new.is_entry));
+        breaking |= old.is_entry;
+    }
+    if old.type_parameters != new.type_parameters {
+        detail.push("type parameters changed".to_owned());
+        breaking = true;
+    }
+    if old.parameters != new.parameters {
+        detail.push("parameters changed".to_owned());
+        breaking = true;
+    }
+    if old.return_ != new.return_ {
+        detail.push("return type changed".to_owned());
+        breaking = true;
+    }
+    (detail.join(", "), breaking)
+}
+
+#[test]
+fn diff_releases() {
+    use crate::release::ReleaseTarget;
+
+    let head = ReleaseTarget::Head.load_bundle().unwrap();
+    assert!(diff_bundles(&head, &head).is_empty());
+
+    let mut old = head.compiled_modules();
+    let new = old.split_off(1);
+    let removed = old[0].self_id().short_str_lossless();
+    let changes = diff_modules(&old, &new);
+    assert!(changes
+        .iter()
+        .any(|c| c.module == removed && c.kind == ChangeKind::Removed && c.breaking));
+}
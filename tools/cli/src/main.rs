@@ -4,11 +4,12 @@ mod ops_cli;
 
 use std::{path::PathBuf, process::exit};
 
-use anyhow::anyhow;
+use anyhow::Context;
 use clap::{Parser, Subcommand};
 use libra_config::config_cli::ConfigCli;
 use libra_query::query_cli::QueryCli;
 use libra_txs::txs_cli::TxsCli;
+use libra_types::core_types::cli_error::CliError;
 use libra_wallet::wallet_cli::WalletCli;
 use node_cli::NodeCli;
 
@@ -22,6 +23,9 @@ struct LibraCli {
     // TODO: change this upstream.
     #[clap(hide(true), long, short = 'f')]
     swarm_config: Option<PathBuf>,
+    /// print errors as a JSON object, including the Move abort location and code when a transaction fails
+    #[clap(long, global = true)]
+    json_errors: bool,
 }
 
 #[derive(Subcommand)]
@@ -38,9 +42,25 @@ enum Sub {
     Version,
 }
 
-fn main() -> anyhow::Result<()> {
+fn main() {
     let cli = LibraCli::parse();
+    let json_errors = cli.json_errors;
 
+    if let Err(e) = run(cli) {
+        let err = CliError::from_anyhow(&e);
+        if json_errors {
+            eprintln!(
+                "{}",
+                serde_json::to_string_pretty(&err).unwrap_or_else(|_| err.to_string())
+            );
+        } else {
+            eprintln!("ERROR: {}", &err);
+        }
+        exit(err.category.exit_code());
+    }
+}
+
+fn run(cli: LibraCli) -> anyhow::Result<()> {
     // Hack. Diem forge API compatibility.
     if let Some(p) = cli.swarm_config {
         let n = NodeCli {
@@ -53,7 +73,7 @@ fn main() -> anyhow::Result<()> {
     match cli.command {
         // Execute Node CLI subcommand
         Some(Sub::Node(n)) => {
-            n.run()?;
+            n.run().context("Failed to execute node tool")?;
         }
         _ => {
             let rt = tokio::runtime::Runtime::new()?;
@@ -61,47 +81,43 @@ fn main() -> anyhow::Result<()> {
                 match cli.command {
                     // Execute Config CLI subcommand
                     Some(Sub::Config(config_cli)) => {
-                        if let Err(e) = config_cli.run().await {
-                            eprintln!("Failed to execute config tool, message: {}", &e);
-                        }
+                        config_cli
+                            .run()
+                            .await
+                            .context("Failed to execute config tool")?;
                     }
 
                     // Execute Move CLI subcommand
                     Some(Sub::Move(move_tool)) => {
-                        if let Err(e) = move_tool
+                        move_tool
                             .execute()
                             .await
-                            .map_err(|e| anyhow!("Failed to execute move tool, message: {}", &e))
-                        {
-                            eprintln!("Failed to execute move tool, message: {}", &e);
-                        }
+                            .context("Failed to execute move tool")?;
                     }
 
                     // Execute Query CLI subcommand
                     Some(Sub::Query(query_cli)) => {
-                        if let Err(e) = query_cli.run().await {
-                            eprintln!("Failed to execute query tool, message: {}", &e);
-                        }
+                        query_cli
+                            .run()
+                            .await
+                            .context("Failed to execute query tool")?;
                     }
 
                     // Execute Transactions CLI subcommand
                     Some(Sub::Txs(txs_cli)) => {
-                        if let Err(e) = txs_cli.run().await {
-                            eprintln!("Failed to execute txs tool, message: {}", &e);
-                        }
+                        txs_cli.run().await.context("Failed to execute txs tool")?;
                     }
 
                     // Execute Wallet CLI subcommand
                     Some(Sub::Wallet(wallet_cli)) => {
-                        if let Err(e) = wallet_cli.run().await {
-                            eprintln!("Failed to execute wallet tool, message: {}", &e);
-                        }
+                        wallet_cli
+                            .run()
+                            .await
+                            .context("Failed to execute wallet tool")?;
                     }
 
                     Some(Sub::Ops(tool)) => {
-                        if let Err(e) = tool.run().await {
-                            eprintln!("Failed to execute ops tool, message: {}", &e);
-                        }
+                        tool.run().await?;
                     }
                     // Display version information
                     Some(Sub::Version) => {
@@ -120,7 +136,8 @@ fn main() -> anyhow::Result<()> {
                         println!("\nliving is easy with eyes closed")
                     }
                 }
-            });
+                Ok::<(), anyhow::Error>(())
+            })?;
         }
     }
 
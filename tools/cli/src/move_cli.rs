@@ -1,9 +1,20 @@
+use anyhow::Context;
 use clap::Subcommand;
 use diem::{
     common::types::CliCommand,
     move_tool::{coverage, CompilePackage, ProvePackage, TestPackage},
 };
-use libra_framework::framework_cli::FrameworkCli;
+use libra_framework::framework_cli::{FrameworkCli, VerifyUpgrade};
+use libra_query::gov_queries::get_proposal;
+use libra_types::{
+    core_types::{
+        config_layers::{ConfigOverrides, ResolvedCfg},
+        proposal_metadata::ProposalMetadata,
+    },
+    exports::Client,
+    type_extensions::client_ext::ClientExt,
+};
+use url::Url;
 
 #[derive(Subcommand)]
 /// Move language tools for Libra core-devs
@@ -20,6 +31,10 @@ pub enum MoveTool {
 impl MoveTool {
     pub async fn execute(self) -> anyhow::Result<()> {
         match self {
+            // the proposal is read from the chain here, libra-framework only compares
+            Self::Framework(FrameworkCli::VerifyUpgrade(tool)) if tool.execution_hash.is_none() => {
+                verify_upgrade_onchain(&tool).await?;
+            }
             Self::Framework(tool) => {
                 tool.execute()?;
             }
@@ -39,3 +54,41 @@ impl MoveTool {
         Ok(())
     }
 }
+
+/// Read the proposal and its published metadata from the chain, and verify
+/// the upgrade against them.
+async fn verify_upgrade_onchain(tool: &VerifyUpgrade) -> anyhow::Result<()> {
+    let client = if let Some(u) = &tool.url {
+        Client::new(Url::parse(u).context("invalid --url")?)
+    } else {
+        let r = ResolvedCfg::load(&ConfigOverrides {
+            config_path: tool.config_path.clone(),
+            ..Default::default()
+        })?;
+        Client::from_libra_config(&r.cfg, Some(r.chain_id)).await?.0
+    };
+    let proposal = get_proposal(&client, tool.proposal_id).await?;
+
+    // the published metadata tells which step differs, if any
+    let published = match (&proposal.metadata_location, &proposal.metadata_hash) {
+        (Some(url), Some(hash)) if !hash.is_empty() => {
+            match ProposalMetadata::fetch(url)
+                .await
+                .and_then(|(meta, h)| ProposalMetadata::check_hash(&h, hash).map(|_| meta))
+            {
+                Ok(meta) => Some(meta),
+                Err(e) => {
+                    println!("WARN: cannot use the proposal metadata, {:#}", e);
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
+
+    tool.verify(
+        &proposal.execution_hash,
+        proposal.is_resolved,
+        published.as_ref(),
+    )
+}
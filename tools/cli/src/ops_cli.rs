@@ -1,3 +1,4 @@
+use anyhow::Context;
 use clap::Subcommand;
 use libra_genesis_tools::cli::GenesisCli;
 use libra_rescue::rescue_cli::RescueCli;
@@ -18,20 +19,16 @@ impl OpsTool {
     pub async fn run(self) -> anyhow::Result<()> {
         match self {
             // Execute Genesis CLI subcommand
-            Self::Genesis(genesis_cli) => {
-                if let Err(e) = genesis_cli.run().await {
-                    eprintln!("Failed to execute genesis tool, message: {}", &e);
-                }
-            }
-            OpsTool::Storage(storage_cli) => {
-                if let Err(e) = storage_cli.run().await {
-                    eprintln!("Failed to execute genesis tool, message: {}", &e);
-                }
-            }
+            Self::Genesis(genesis_cli) => genesis_cli
+                .run()
+                .await
+                .context("Failed to execute genesis tool")?,
+            OpsTool::Storage(storage_cli) => storage_cli
+                .run()
+                .await
+                .context("Failed to execute storage tool")?,
             OpsTool::Rescue(rescue_cli) => {
-                if let Err(e) = rescue_cli.run() {
-                    eprintln!("Failed to execute genesis tool, message: {}", &e);
-                }
+                rescue_cli.run().context("Failed to execute rescue tool")?
             }
         };
         Ok(())
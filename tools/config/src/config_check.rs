@@ -0,0 +1,167 @@
+//! `config migrate` and `config check`: upgrade an older config file, and
+//! report the problems of a config file before they fail a transaction.
+
+use anyhow::{bail, Context, Result};
+use libra_types::{
+    core_types::{
+        app_cfg::{default_file_path, AppCfg, CONFIG_FILE_NAME, CONFIG_VERSION},
+        config_migration::{
+            accounts_not_derived, migrate_legacy_toml, migrate_yaml, unknown_fields, Migrated,
+        },
+    },
+    exports::Client,
+    global_config_dir,
+    type_extensions::client_ext::ClientExt,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// How long to wait for a node of the playlist
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Upgrade a 0L.toml or a libra-cli-config.yaml to the current version. The
+/// yaml file is upgraded in place, and the old one is kept as a .bak file.
+/// Returns the path of the new file.
+pub fn migrate(from: Option<PathBuf>, force: bool) -> Result<PathBuf> {
+    let from = from.unwrap_or_else(|| {
+        let yaml = default_file_path();
+        if yaml.exists() {
+            yaml
+        } else {
+            global_config_dir().join("0L.toml")
+        }
+    });
+    let raw = fs::read_to_string(&from)
+        .context(format!("could not read the config file {}", from.display()))?;
+
+    let is_toml = from.extension().map(|e| e == "toml").unwrap_or(false);
+    let (Migrated { cfg, notes }, to) = if is_toml {
+        let node_home = global_config_dir();
+        let to = node_home.join(CONFIG_FILE_NAME);
+        if to.exists() && !force {
+            bail!(
+                "{} already exists, use --force to overwrite it",
+                to.display()
+            );
+        }
+        (migrate_legacy_toml(&raw, node_home)?, to)
+    } else {
+        (migrate_yaml(&raw)?, from.clone())
+    };
+
+    if notes.is_empty() {
+        println!("{} is up to date", from.display());
+        return Ok(to);
+    }
+    for n in &notes {
+        println!("- {}", n);
+    }
+
+    if to.exists() {
+        let backup = to.with_extension("yaml.bak");
+        fs::copy(&to, &backup)?;
+        println!("the old file is saved as {}", backup.display());
+    }
+    if let Some(dir) = to.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&to, serde_yaml::to_string(&cfg)?)?;
+    Ok(to)
+}
+
+/// Report the problems of a config file. Returns how many were found.
+pub async fn check(path: Option<PathBuf>) -> Result<usize> {
+    let path = path.unwrap_or_else(default_file_path);
+    let raw = fs::read_to_string(&path)
+        .context(format!("could not read the config file {}", path.display()))?;
+    let mut problems = vec![];
+
+    let cfg: AppCfg = match serde_yaml::from_str(&raw) {
+        Ok(c) => c,
+        Err(e) => bail!(
+            "{} is not a valid config, try `libra config migrate`: {}",
+            path.display(),
+            e
+        ),
+    };
+    if cfg.config_version < CONFIG_VERSION {
+        problems.push(format!(
+            "config version {} is older than {}, run `libra config migrate`",
+            cfg.config_version, CONFIG_VERSION
+        ));
+    }
+    for f in unknown_fields(&raw)? {
+        problems.push(format!("unknown field {}, it is ignored", f));
+    }
+
+    // every node should answer, on the chain of its playlist
+    let mut clients = vec![];
+    for np in &cfg.network_playlist {
+        for h in &np.nodes {
+            let client = Client::new(h.url.clone());
+            match tokio::time::timeout(CHECK_TIMEOUT, client.get_index()).await {
+                Ok(Ok(res)) => {
+                    let chain_id = res.into_inner().chain_id;
+                    if chain_id != np.chain_name.id() {
+                        problems.push(format!(
+                            "{} is in the {} playlist, but it is on chain id {}",
+                            h.url, np.chain_name, chain_id
+                        ));
+                    } else {
+                        clients.push((np.chain_name, client));
+                    }
+                }
+                _ => problems.push(format!("{} is unreachable", h.url)),
+            }
+        }
+    }
+
+    if cfg.get_profile(None).is_err() {
+        problems.push("there is no default profile".to_owned());
+    }
+    for p in &cfg.user_profiles {
+        let chain = p.chain_id.unwrap_or(cfg.workspace.default_chain_id);
+        if !cfg.network_playlist.iter().any(|np| np.chain_name == chain) {
+            problems.push(format!(
+                "profile {} is on {}, which has no network playlist",
+                p.nickname, chain
+            ));
+        }
+    }
+
+    // a rotated key is fine, if the chain says the account has it
+    for p in accounts_not_derived(&cfg) {
+        let chain = p.chain_id.unwrap_or(cfg.workspace.default_chain_id);
+        let rotated = match clients.iter().find(|(c, _)| *c == chain) {
+            Some((_, client)) => client
+                .lookup_originating_address(p.auth_key)
+                .await
+                .map(|a| a == p.account)
+                .unwrap_or(false),
+            None => false,
+        };
+        if !rotated {
+            problems.push(format!(
+                "profile {}: account {} does not match auth key {}",
+                p.nickname, p.account, p.auth_key
+            ));
+        }
+    }
+
+    print_report(&path, &problems);
+    Ok(problems.len())
+}
+
+fn print_report(path: &Path, problems: &[String]) {
+    if problems.is_empty() {
+        println!("{} looks good", path.display());
+        return;
+    }
+    println!("{} problems in {}:", problems.len(), path.display());
+    for p in problems {
+        println!("- {}", p);
+    }
+}
@@ -1,12 +1,16 @@
 use crate::{
-    config_wizard,
+    config_check, config_wizard,
     make_yaml_public_fullnode::{download_genesis, get_genesis_waypoint, init_fullnode_yaml},
+    profile_cli::ProfileSub,
     validator_config::{validator_dialogue, vfn_dialogue},
 };
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use clap::Parser;
 use libra_types::{
-    core_types::app_cfg::{self, AppCfg},
+    core_types::{
+        app_cfg::{self, AppCfg},
+        config_layers::{ConfigOverrides, ResolvedCfg},
+    },
     exports::{AccountAddress, AuthenticationKey, Client, NamedChain},
     global_config_dir, ol_progress,
     type_extensions::client_ext::ClientExt,
@@ -62,7 +66,31 @@ enum ConfigSub {
         force_url: Option<Url>,
     },
     /// Show the addresses and configs on this device
-    View {},
+    View {
+        /// show the effective config of the tools instead, after the LIBRA_* environment variables and the flags, and where each value came from
+        #[clap(long)]
+        resolved: bool,
+    },
+
+    /// Add, list, select or remove the account profiles
+    #[clap(subcommand)]
+    Profile(ProfileSub),
+
+    /// Upgrade an older libra-cli-config.yaml, or the 0L.toml of v5 and v6, to the current config version
+    Migrate {
+        /// optional, the file to upgrade. Otherwise libra-cli-config.yaml, or 0L.toml if there is none
+        #[clap(long)]
+        from: Option<PathBuf>,
+        /// overwrite libra-cli-config.yaml when upgrading a 0L.toml
+        #[clap(long)]
+        force: bool,
+    },
+
+    /// Find problems in libra-cli-config.yaml: unknown fields, unreachable nodes, nodes on the wrong chain, and accounts which do not match their keys
+    Check {},
+
+    /// Check the health of the nodes in the network playlist, rank them, and save the results to libra-cli-config.yaml
+    CheckPlaylist {},
 
     // COMMIT NOTE: we haven't used vendor tooling configs for anything.
     /// Generate validators' config file
@@ -105,10 +133,9 @@ impl ConfigCli {
                 let profile = if *reset_address {
                     let mut account_keys = config_wizard::prompt_for_account()?;
 
-                    let client = Client::new(cfg.pick_url(self.chain_name)?);
-
-                    // Lookup originating address if client index is successful
-                    if client.get_index().await.is_ok() {
+                    // Lookup originating address if an upstream node is reachable
+                    if let Ok((client, _)) = Client::from_libra_config(&cfg, self.chain_name).await
+                    {
                         account_keys.account = match client
                             .lookup_originating_address(account_keys.auth_key)
                             .await
@@ -171,6 +198,23 @@ impl ConfigCli {
                 Ok(())
             }
 
+            Some(ConfigSub::Profile(sub)) => sub.run(self.path.clone()).await,
+
+            Some(ConfigSub::Migrate { from, force }) => {
+                let to = config_check::migrate(from.to_owned(), *force)?;
+                println!("SUCCESS: config saved at {}", to.display());
+                Ok(())
+            }
+
+            Some(ConfigSub::Check {}) => {
+                let problems = config_check::check(self.path.clone()).await?;
+                if problems > 0 {
+                    bail!("found {} problems in the config", problems);
+                }
+                println!("SUCCESS: no problems found in the config");
+                Ok(())
+            }
+
             // Initialize configuration wizard
             Some(ConfigSub::Init {
                 force_address,
@@ -216,8 +260,48 @@ impl ConfigCli {
                 Ok(())
             }
 
+            // Refresh the sync status of the playlist, and save it
+            Some(ConfigSub::CheckPlaylist {}) => {
+                let mut cfg = AppCfg::load(self.path.clone())
+                    .map_err(|e| anyhow!("no config file found for libra tools, {}", e))?;
+
+                let np = cfg
+                    .refresh_network_profile_and_save(self.chain_name)
+                    .await?;
+
+                println!("network playlist for {}, healthiest first:", np.chain_name);
+                for h in &np.nodes {
+                    let status = if h.is_sync {
+                        "synced"
+                    } else if h.is_api {
+                        "behind"
+                    } else {
+                        "unreachable"
+                    };
+                    println!(
+                        "- {} {}: version {}, latency {}ms",
+                        status, h.url, h.version, h.latency_ms
+                    );
+                }
+                println!("will use: {}", cfg.pick_url(self.chain_name)?);
+                Ok(())
+            }
+
             // View validator and network configurations
-            Some(ConfigSub::View {}) => {
+            Some(ConfigSub::View { resolved: true }) => {
+                let r = ResolvedCfg::load(&ConfigOverrides {
+                    config_path: self.path.clone(),
+                    chain_id: self.chain_name,
+                    profile: self.profile.clone(),
+                    ..Default::default()
+                })?;
+                for (key, value, source) in r.report() {
+                    println!("{}: {} ({})", key, value, source);
+                }
+                Ok(())
+            }
+
+            Some(ConfigSub::View { resolved: false }) => {
                 let home_dir = self.path.clone().unwrap_or_else(global_config_dir);
 
                 let public_keys_file = home_dir.join(OPERATOR_FILE);
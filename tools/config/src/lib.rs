@@ -1,7 +1,9 @@
+pub mod config_check;
 pub mod config_cli;
 pub mod config_wizard;
 pub mod make_profile; // TODO: deprecated?
 pub mod make_yaml_public_fullnode;
 pub mod make_yaml_validator;
+pub mod profile_cli;
 pub mod validator_config;
 pub mod validator_registration;
@@ -0,0 +1,130 @@
+//! Manage the profiles of libra-cli-config.yaml, e.g. an owner account, an
+//! operator account and community wallets on the same machine.
+
+use anyhow::{bail, Context, Result};
+use libra_types::{
+    core_types::app_cfg::{AppCfg, Profile},
+    exports::{AccountAddress, AuthenticationKey, Client, NamedChain},
+    type_extensions::client_ext::ClientExt,
+};
+use libra_wallet::{account_keys::get_account_at_index, load_keys::get_account_from_prompt};
+use std::path::PathBuf;
+
+#[derive(clap::Subcommand)]
+pub enum ProfileSub {
+    /// Add a profile, from its address and auth key or from the mnemonic
+    Add {
+        /// a name for the profile, used with --profile
+        #[clap(short, long)]
+        nickname: String,
+        /// the account address, requires --auth-key. Otherwise from the mnemonic
+        #[clap(short, long, requires = "auth_key")]
+        address: Option<AccountAddress>,
+        /// the auth key of the account, requires --address
+        #[clap(long, requires = "address")]
+        auth_key: Option<AuthenticationKey>,
+        /// optional, the child number of the key in the mnemonic, otherwise the owner key 0
+        #[clap(short, long)]
+        index: Option<u64>,
+        /// optional, the network of this profile. Otherwise the default chain of the config
+        #[clap(short, long)]
+        chain: Option<NamedChain>,
+        /// make it the default profile
+        #[clap(short, long)]
+        default: bool,
+    },
+    /// List the profiles
+    List,
+    /// Make a profile the default one
+    Use {
+        /// nickname or address of the profile
+        nickname: String,
+    },
+    /// Remove a profile
+    Remove {
+        /// nickname or address of the profile
+        nickname: String,
+    },
+}
+
+impl ProfileSub {
+    pub async fn run(&self, config_path: Option<PathBuf>) -> Result<()> {
+        let mut cfg = AppCfg::load(config_path).context("no config file found for libra tools")?;
+        match self {
+            ProfileSub::Add {
+                nickname,
+                address,
+                auth_key,
+                index,
+                chain,
+                default,
+            } => {
+                if cfg.user_profiles.iter().any(|p| &p.nickname == nickname) {
+                    bail!("there is already a profile named {}", nickname);
+                }
+                let mut profile = match (address, auth_key) {
+                    (Some(a), Some(k)) => Profile::new(*k, *a),
+                    _ => {
+                        let (_, _, wallet) = get_account_from_prompt();
+                        let keys = get_account_at_index(&wallet, index.unwrap_or(0))?;
+                        // the account may have rotated its key to this one
+                        let account = match Client::from_libra_config(&cfg, *chain).await {
+                            Ok((client, _)) => client
+                                .lookup_originating_address(keys.auth_key)
+                                .await
+                                .unwrap_or(keys.account),
+                            Err(_) => keys.account,
+                        };
+                        Profile::new(keys.auth_key, account)
+                    }
+                };
+                profile.nickname.clone_from(nickname);
+                profile.derivation_index = index.filter(|i| *i != 0);
+                profile.chain_id = *chain;
+                let account = profile.account;
+                cfg.maybe_add_profile(profile)?;
+                if *default {
+                    cfg.set_default_profile(&account.to_hex_literal())?;
+                }
+                cfg.save_file()?;
+                println!(
+                    "SUCCESS: added profile {} for account {}",
+                    nickname, account
+                );
+            }
+            ProfileSub::List => {
+                if cfg.user_profiles.is_empty() {
+                    println!("no profiles found");
+                }
+                let default = cfg.get_profile(None).ok().map(|p| p.account);
+                for p in &cfg.user_profiles {
+                    let marker = if Some(p.account) == default { "*" } else { " " };
+                    let chain = p.chain_id.unwrap_or(cfg.workspace.default_chain_id);
+                    let index = p
+                        .derivation_index
+                        .map(|i| format!(", key index: {}", i))
+                        .unwrap_or_default();
+                    println!(
+                        "{} {}: {}, chain: {}{}",
+                        marker, p.nickname, p.account, chain, index
+                    );
+                }
+            }
+            ProfileSub::Use { nickname } => {
+                cfg.set_default_profile(nickname)?;
+                cfg.save_file()?;
+                println!(
+                    "SUCCESS: default profile is now {}",
+                    cfg.get_profile(None)?.nickname
+                );
+            }
+            ProfileSub::Remove { nickname } => {
+                cfg.try_remove_profile(nickname)
+                    .context(format!("no profile found matching {}", nickname))?;
+                cfg.save_file()?;
+                println!("SUCCESS: removed profile {}", nickname);
+            }
+        }
+        Ok(())
+    }
+}
@@ -25,8 +25,12 @@ pub async fn initialize_validator(
     keep_legacy_address: bool,
     chain_name: Option<NamedChain>,
 ) -> anyhow::Result<PublicIdentity> {
-    let (.., pub_id, keys) =
-        libra_wallet::keys::refresh_validator_files(mnem, home_path.clone(), keep_legacy_address)?;
+    let (.., pub_id, keys) = libra_wallet::keys::refresh_validator_files(
+        mnem,
+        home_path.clone(),
+        keep_legacy_address,
+        "",
+    )?;
     OLProgress::complete("initialized validator key files");
 
     // TODO: set validator fullnode configs. Not NONE
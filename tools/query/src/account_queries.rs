@@ -1,14 +1,22 @@
 //! Helper functions for querying account-related data using the Diem SDK client.
 
+use anyhow::{bail, Context};
 use diem_sdk::{
     rest_client::{
-        diem_api_types::{Transaction, VersionedEvent, ViewRequest},
+        diem_api_types::{
+            EntryFunctionId, EntryFunctionPayload, Transaction, TransactionPayload, VersionedEvent,
+            ViewRequest,
+        },
         Client,
     },
     types::{account_address::AccountAddress, validator_config::ValidatorConfig},
 };
 use libra_types::{
-    move_resource::{gas_coin::SlowWalletBalance, txschedule::TxSchedule},
+    core_types::error_map::{explain_vm_status, ErrorMaps},
+    move_resource::{
+        donor_voice_txs::{MultiAuthSigners, TxScheduleDisplay, TxScheduleResource, VetoTally},
+        gas_coin::SlowWalletBalance,
+    },
     type_extensions::client_ext::{entry_function_id, ClientExt},
 };
 use serde_json::{json, Value};
@@ -64,20 +72,191 @@ pub async fn get_events(
     Ok(res)
 }
 
-/// Retrieves transactions associated with a given account.
+/// The REST API's default page size, returned when no count is given.
+const TXS_DEFAULT_COUNT: u64 = 25;
+/// The REST API's maximum page size for transactions.
+const TXS_PAGE_SIZE: u64 = 100;
+
+/// Kinds of transactions, for filtering the transaction history.
+/// Only user transactions are sent by an account, the other kinds are read
+/// from the transactions of the whole chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[clap(rename_all = "kebab_case")]
+pub enum TxKind {
+    /// transactions signed by a user
+    User,
+    /// the genesis transaction
+    Genesis,
+    /// block prologue
+    BlockMetadata,
+    /// end of block checkpoint
+    StateCheckpoint,
+}
+
+impl TxKind {
+    /// does the transaction belong to this kind
+    pub fn matches(&self, tx: &Transaction) -> bool {
+        matches!(
+            (self, tx),
+            (TxKind::User, Transaction::UserTransaction(_))
+                | (TxKind::Genesis, Transaction::GenesisTransaction(_))
+                | (
+                    TxKind::BlockMetadata,
+                    Transaction::BlockMetadataTransaction(_)
+                )
+                | (
+                    TxKind::StateCheckpoint,
+                    Transaction::StateCheckpointTransaction(_)
+                )
+        )
+    }
+}
+
+/// Filters for an account's transaction history. Empty fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct TxsFilter {
+    /// only this kind of transaction
+    pub kind: Option<TxKind>,
+    /// only transactions calling this entry function e.g. 0x1::ol_account::transfer
+    pub function: Option<EntryFunctionId>,
+}
+
+impl TxsFilter {
+    /// true if no filter is set
+    pub fn is_empty(&self) -> bool {
+        self.kind.is_none() && self.function.is_none()
+    }
+
+    /// does the transaction pass all the filters
+    pub fn matches(&self, tx: &Transaction) -> bool {
+        if let Some(k) = &self.kind {
+            if !k.matches(tx) {
+                return false;
+            }
+        }
+        if let Some(f) = &self.function {
+            return entry_function_payload(tx).is_some_and(|p| &p.function == f);
+        }
+        true
+    }
+}
+
+/// The entry function payload of a user transaction, if any.
+pub fn entry_function_payload(tx: &Transaction) -> Option<&EntryFunctionPayload> {
+    match tx {
+        Transaction::UserTransaction(u) => match &u.request.payload {
+            TransactionPayload::EntryFunctionPayload(p) => Some(p),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// A summary of a transaction for display: the kind, sender, entry function
+/// called, its arguments, the gas used and the VM status, with the abort
+/// explained by the framework error maps when they are loaded.
+pub fn transaction_summary(tx: &Transaction, maps: Option<&ErrorMaps>) -> Value {
+    let sender = match tx {
+        Transaction::UserTransaction(u) => Some(u.request.sender.to_string()),
+        _ => None,
+    };
+    let payload = entry_function_payload(tx);
+    let gas_used: Option<u64> = tx.transaction_info().ok().map(|i| i.gas_used.into());
+
+    json!({
+        "type": tx.type_str(),
+        "version": tx.version(),
+        "timestamp": tx.timestamp(),
+        "is_pending": tx.is_pending(),
+        "sender": sender,
+        "function": payload.map(|p| p.function.to_string()),
+        "type_arguments": payload.map(|p| &p.type_arguments),
+        "arguments": payload.map(|p| &p.arguments),
+        "gas_used": gas_used,
+        "success": tx.success(),
+        "vm_status": tx.vm_status(),
+        "abort": maps.and_then(|m| explain_vm_status(m, &tx.vm_status())),
+    })
+}
+
+/// Retrieves transactions associated with a given account, paging through the
+/// REST API as needed. For the kinds which are not user transactions the
+/// transactions of the whole chain are read instead.
+/// If `txs_height` (a sequence number, or a version for the whole chain) is
+/// given, returns the first `txs_count` matching transactions from that height,
+/// otherwise the latest `txs_count` matching transactions. The default count
+/// is 25, as in the REST API.
 pub async fn get_transactions(
     client: &Client,
     account: AccountAddress,
     txs_height: Option<u64>,
     txs_count: Option<u64>,
-    _txs_type: Option<String>,
+    filter: &TxsFilter,
 ) -> anyhow::Result<Vec<Transaction>> {
-    // TODO: filter by type (what type is it?)
-    let res = client
-        .get_account_transactions(account, txs_height, txs_count)
-        .await?
-        .into_inner();
-    Ok(res)
+    let count = txs_count.unwrap_or(TXS_DEFAULT_COUNT) as usize;
+    let sender = match filter.kind {
+        None | Some(TxKind::User) => Some(account),
+        // there is only one, at version 0
+        Some(TxKind::Genesis) => {
+            let genesis = client.get_transaction_by_version(0).await?.into_inner();
+            return Ok([genesis]
+                .into_iter()
+                .filter(|tx| filter.matches(tx))
+                .collect());
+        }
+        Some(_) => None,
+    };
+
+    let mut found = vec![];
+    if let Some(mut start) = txs_height {
+        // oldest first, from the height
+        while found.len() < count {
+            let page = get_transactions_page(client, sender, start).await?;
+            let page_len = page.len() as u64;
+            found.extend(page.into_iter().filter(|tx| filter.matches(tx)));
+            if page_len < TXS_PAGE_SIZE {
+                break;
+            }
+            start += page_len;
+        }
+        found.truncate(count);
+    } else {
+        // latest first, paging backward from the newest
+        let mut end = match sender {
+            Some(a) => client.get_account(a).await?.into_inner().sequence_number,
+            None => client.get_ledger_information().await?.into_inner().version + 1,
+        };
+        while found.len() < count && end > 0 {
+            let start = end.saturating_sub(TXS_PAGE_SIZE);
+            let mut page = get_transactions_page(client, sender, start).await?;
+            page.truncate((end - start) as usize);
+            found.extend(page.into_iter().rev().filter(|tx| filter.matches(tx)));
+            end = start;
+        }
+        found.truncate(count);
+        found.reverse();
+    }
+
+    Ok(found)
+}
+
+/// A page of the transactions of an account, or of the whole chain.
+async fn get_transactions_page(
+    client: &Client,
+    sender: Option<AccountAddress>,
+    start: u64,
+) -> anyhow::Result<Vec<Transaction>> {
+    let page = match sender {
+        Some(a) => client
+            .get_account_transactions(a, Some(start), Some(TXS_PAGE_SIZE))
+            .await?
+            .into_inner(),
+        None => client
+            .get_transactions(Some(start), Some(TXS_PAGE_SIZE as u16))
+            .await?
+            .into_inner(),
+    };
+    Ok(page)
 }
 
 /// Checks if the community wallet for a given account has been migrated.
@@ -96,29 +275,124 @@ pub async fn is_community_wallet_migrated(
     Ok(json!(res))
 }
 
-/// Retrieves signers for the community wallet associated with a given account.
+/// Retrieves signers for the community wallet associated with a given account,
+/// and the n-of-m threshold required to approve a transaction.
 pub async fn community_wallet_signers(
     client: &Client,
     account: AccountAddress,
-) -> anyhow::Result<serde_json::Value> {
-    //they are empty for now
-    let community_wallet_migrated_id = entry_function_id("multi_action", "get_authorities")?;
+) -> anyhow::Result<MultiAuthSigners> {
     let request = ViewRequest {
-        function: community_wallet_migrated_id,
+        function: entry_function_id("multi_action", "get_authorities")?,
         type_arguments: vec![],
         arguments: vec![account.to_string().into()],
     };
+    let res = client.view(&request, None).await?.into_inner();
+    let authorities: Vec<AccountAddress> = serde_json::from_value(
+        res.first()
+            .context("get_authorities returned no value")?
+            .clone(),
+    )?;
 
+    let request = ViewRequest {
+        function: entry_function_id("multi_action", "get_threshold")?,
+        type_arguments: vec![],
+        arguments: vec![account.to_string().into()],
+    };
     let res = client.view(&request, None).await?.into_inner();
-    Ok(json!(res))
+    if res.len() != 2 {
+        bail!("get_threshold returned an unexpected value: {:?}", res);
+    }
+    let n_required = serde_json::from_value::<String>(res[0].clone())?.parse::<u64>()?;
+    let m_total = serde_json::from_value::<String>(res[1].clone())?.parse::<u64>()?;
+
+    Ok(MultiAuthSigners {
+        authorities,
+        n_required,
+        m_total,
+    })
 }
 
-/// Retrieves scheduled transactions for the community wallet associated with a given account.
+/// Retrieves the raw transfer schedule (scheduled, vetoed, paid) of a Donor Voice account.
 pub async fn community_wallet_scheduled_transactions(
     client: &Client,
     account: AccountAddress,
-) -> anyhow::Result<TxSchedule> {
-    client.get_move_resource::<TxSchedule>(account).await
+) -> anyhow::Result<TxScheduleResource> {
+    // NOTE: the resource lives in the donor_voice_txs module, the MoveStructType
+    // of TxScheduleResource is kept for reading legacy state.
+    let res = client
+        .get_account_resource_bcs::<TxScheduleResource>(account, "0x1::donor_voice_txs::TxSchedule")
+        .await?
+        .into_inner();
+    Ok(res)
+}
+
+/// Gets the donors' veto tally for a scheduled transfer, if a veto vote was started.
+pub async fn community_wallet_veto_tally(
+    client: &Client,
+    account: AccountAddress,
+    uid: u64,
+) -> anyhow::Result<Option<VetoTally>> {
+    let request = ViewRequest {
+        function: entry_function_id("donor_voice_governance", "tx_has_veto")?,
+        type_arguments: vec![],
+        arguments: vec![account.to_string().into(), uid.to_string().into()],
+    };
+    let res = client.view(&request, None).await?.into_inner();
+    let has_veto: bool = serde_json::from_value(
+        res.first()
+            .context("tx_has_veto returned no value")?
+            .clone(),
+    )?;
+    // get_veto_tally aborts if there is no ballot
+    if !has_veto {
+        return Ok(None);
+    }
+
+    let request = ViewRequest {
+        function: entry_function_id("donor_voice_governance", "get_veto_tally")?,
+        type_arguments: vec![],
+        arguments: vec![account.to_string().into(), uid.to_string().into()],
+    };
+    let res = client.view(&request, None).await?.into_inner();
+    if res.len() != 2 {
+        bail!("get_veto_tally returned an unexpected value: {:?}", res);
+    }
+    let approval_pct = serde_json::from_value::<String>(res[0].clone())?.parse::<u64>()?;
+    let threshold = serde_json::from_value::<String>(res[1].clone())?.parse::<u64>()?;
+
+    Ok(Some(VetoTally {
+        approval_pct,
+        threshold,
+    }))
+}
+
+/// Retrieves the transfers of a Donor Voice account in human readable form,
+/// including the veto tally of the transfers which have not been paid.
+pub async fn community_wallet_pending_transactions(
+    client: &Client,
+    account: AccountAddress,
+) -> anyhow::Result<TxScheduleDisplay> {
+    let schedule = community_wallet_scheduled_transactions(client, account).await?;
+
+    let mut scheduled = vec![];
+    for t in schedule.scheduled.iter() {
+        let tally = community_wallet_veto_tally(client, account, t.uid.creation_num).await?;
+        scheduled.push(t.to_display(tally));
+    }
+
+    let mut veto = vec![];
+    for t in schedule.veto.iter() {
+        let tally = community_wallet_veto_tally(client, account, t.uid.creation_num).await?;
+        veto.push(t.to_display(tally));
+    }
+
+    let paid = schedule.paid.iter().map(|t| t.to_display(None)).collect();
+
+    Ok(TxScheduleDisplay {
+        scheduled,
+        veto,
+        paid,
+    })
 }
 
 /// Retrieves all multi_auth actions (pending, approved, expired) for a given multi_auth account.
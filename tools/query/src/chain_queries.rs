@@ -3,7 +3,10 @@
 use crate::query_view::{self, get_view};
 use anyhow::Context;
 use diem_sdk::rest_client::{diem_api_types::ViewRequest, Client};
-use libra_types::type_extensions::client_ext::entry_function_id;
+use libra_types::{
+    core_types::network_playlist::NetworkPlaylist, type_extensions::client_ext::entry_function_id,
+};
+use serde_json::{json, Value};
 
 /// Retrieves the current epoch from the blockchain.
 pub async fn get_epoch(client: &Client) -> anyhow::Result<u64> {
@@ -72,7 +75,10 @@ pub async fn can_gov_proposal_resolve(client: &Client, id: u64) -> anyhow::Resul
     )
     .await?;
 
-    serde_json::from_value::<bool>(query_res).context("cannot parse api res")
+    serde_json::from_value::<Vec<bool>>(query_res)?
+        .into_iter()
+        .next()
+        .context("could not get a response from view function get_can_resolve")
 }
 
 // TODO: code duplication
@@ -103,7 +109,47 @@ pub async fn get_gov_proposal_votes(client: &Client, id: u64) -> anyhow::Result<
     )
     .await?;
 
-    Ok(serde_json::from_value::<Vec<u128>>(query_res)?)
+    // the api returns u128 as strings
+    serde_json::from_value::<Vec<String>>(query_res)?
+        .iter()
+        .map(|v| v.parse().context("cannot parse the votes"))
+        .collect()
+}
+
+/// Retrieves the state of a governance proposal: pending (0), succeeded (1) or failed (3).
+pub async fn get_gov_proposal_state(client: &Client, id: u64) -> anyhow::Result<u64> {
+    let query_res = query_view::get_view(
+        client,
+        "0x1::diem_governance::get_proposal_state",
+        None,
+        Some(id.to_string()),
+    )
+    .await?;
+
+    serde_json::from_value::<Vec<String>>(query_res)?
+        .first()
+        .context("could not get a response from view function get_proposal_state")?
+        .parse()
+        .context("cannot parse the proposal state")
+}
+
+/// Retrieves the execution hash of the next script of a governance proposal,
+/// as hex. It is empty when there is no step to resolve.
+pub async fn get_gov_proposal_approved_hash(client: &Client, id: u64) -> anyhow::Result<String> {
+    let query_res = query_view::get_view(
+        client,
+        "0x1::diem_governance::get_approved_hash",
+        None,
+        Some(id.to_string()),
+    )
+    .await?;
+
+    // the api returns vector<u8> as a hex string
+    Ok(serde_json::from_value::<Vec<String>>(query_res)?
+        .first()
+        .context("could not get a response from view function get_approved_hash")?
+        .trim_start_matches("0x")
+        .to_owned())
 }
 
 /// Retrieves the current blockchain height.
@@ -115,3 +161,52 @@ pub async fn get_height(client: &Client) -> anyhow::Result<u64> {
 
     Ok(height)
 }
+
+/// Compares the local node's ledger version and timestamp with each of the
+/// upstream nodes in the network playlist. Returns the lag per peer, in
+/// versions and in seconds. A positive lag means the local node is behind.
+pub async fn get_sync_delay(
+    local: &Client,
+    mut playlist: NetworkPlaylist,
+) -> anyhow::Result<Value> {
+    let index = local
+        .get_index()
+        .await
+        .context("cannot connect to the local node")?
+        .into_inner();
+    let local_version: u64 = index.ledger_version.into();
+    let local_timestamp: u64 = index.ledger_timestamp.into();
+
+    playlist.refresh_sync_status().await?;
+
+    let upstream: Vec<Value> = playlist
+        .nodes
+        .iter()
+        .map(|p| {
+            if !p.is_api {
+                return json!({
+                  "url": p.url,
+                  "note": p.note,
+                  "is_api": false,
+                });
+            }
+            json!({
+              "url": p.url,
+              "note": p.note,
+              "is_api": true,
+              "is_sync": p.is_sync,
+              "version": p.version,
+              "version_lag": p.version as i64 - local_version as i64,
+              "seconds_lag": (p.ledger_timestamp as i64 - local_timestamp as i64) / 1_000_000,
+            })
+        })
+        .collect();
+
+    Ok(json!({
+      "local": {
+        "version": local_version,
+        "ledger_timestamp": local_timestamp,
+      },
+      "upstream": upstream,
+    }))
+}
@@ -0,0 +1,254 @@
+//! Governance proposals: their state, and how each account voted.
+//!
+//! The proposals are read from the table of the voting forum at 0x1, since
+//! the view functions only return a field at a time. The votes per account
+//! are only found in the vote events of diem_governance.
+
+use crate::chain_queries::{
+    can_gov_proposal_resolve, get_gov_proposal_state, get_next_governance_proposal_id,
+};
+use anyhow::{bail, Context};
+use diem_sdk::{rest_client::Client, types::account_address::AccountAddress};
+use libra_types::core_types::proposal_metadata::ProposalMetadata;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::str::FromStr;
+
+const VOTING_FORUM: &str = "0x1::voting::VotingForum<0x1::governance_proposal::GovernanceProposal>";
+const PROPOSAL: &str = "0x1::voting::Proposal<0x1::governance_proposal::GovernanceProposal>";
+const GOVERNANCE_EVENTS: &str = "0x1::diem_governance::GovernanceEvents";
+
+/// The REST API's maximum page size for events.
+const EVENTS_PAGE_SIZE: u16 = 100;
+
+/// same as the PROPOSAL_STATE_* constants of voting.move
+const PROPOSAL_STATE_PENDING: u64 = 0;
+const PROPOSAL_STATE_SUCCEEDED: u64 = 1;
+
+/// A governance proposal, as stored in the voting forum.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProposalSummary {
+    pub id: u64,
+    pub proposer: AccountAddress,
+    pub execution_hash: String,
+    pub metadata_location: Option<String>,
+    pub metadata_hash: Option<String>,
+    pub yes_votes: u128,
+    pub no_votes: u128,
+    /// the participation needed for the proposal to pass
+    pub min_vote_threshold: u128,
+    pub creation_time_secs: u64,
+    /// voting closes at this unix time
+    pub expiration_secs: u64,
+    pub is_resolved: bool,
+    /// voting, passed, failed, resolvable or resolved
+    pub status: String,
+}
+
+/// A vote on a proposal, from the vote events.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProposalVote {
+    pub voter: AccountAddress,
+    /// the voting power, which is the balance of the voter
+    pub num_votes: u64,
+    pub should_pass: bool,
+    /// the ledger version of the vote
+    pub version: u64,
+}
+
+impl ProposalSummary {
+    /// read the proposal from the json of the table item
+    pub fn from_json(id: u64, v: &Value) -> anyhow::Result<Self> {
+        let metadata = |key: &str| -> Option<String> {
+            v["metadata"]["data"]
+                .as_array()?
+                .iter()
+                .find(|e| e["key"] == key)
+                .and_then(|e| e["value"].as_str())
+                .and_then(|hex_str| hex::decode(hex_str.trim_start_matches("0x")).ok())
+                .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+        };
+
+        let is_resolved = v["is_resolved"]
+            .as_bool()
+            .context("proposal has no is_resolved")?;
+        Ok(Self {
+            id,
+            proposer: field(v, "proposer")?,
+            execution_hash: v["execution_hash"]
+                .as_str()
+                .context("proposal has no execution_hash")?
+                .to_owned(),
+            metadata_location: metadata("metadata_location"),
+            metadata_hash: metadata("metadata_hash"),
+            yes_votes: field(v, "yes_votes")?,
+            no_votes: field(v, "no_votes")?,
+            min_vote_threshold: field(v, "min_vote_threshold")?,
+            creation_time_secs: field(v, "creation_time_secs")?,
+            expiration_secs: field(v, "expiration_secs")?,
+            is_resolved,
+            status: if is_resolved { "resolved" } else { "voting" }.to_owned(),
+        })
+    }
+}
+
+/// a field of a move struct, which the api returns as a string
+fn field<T: FromStr>(v: &Value, name: &str) -> anyhow::Result<T> {
+    v[name]
+        .as_str()
+        .context(format!("proposal has no {}", name))?
+        .parse()
+        .map_err(|_| anyhow::anyhow!("cannot parse {} of the proposal", name))
+}
+
+/// The handle of the table of proposals.
+async fn proposals_table(client: &Client) -> anyhow::Result<AccountAddress> {
+    let res = client
+        .get_account_resource(AccountAddress::ONE, VOTING_FORUM)
+        .await?
+        .into_inner()
+        .context("no governance voting forum found at 0x1")?;
+    field(&res.data["proposals"], "handle")
+}
+
+/// Get a proposal, and whether it can be resolved now.
+pub async fn get_proposal(client: &Client, id: u64) -> anyhow::Result<ProposalSummary> {
+    let table = proposals_table(client).await?;
+    get_proposal_from_table(client, table, id).await
+}
+
+async fn get_proposal_from_table(
+    client: &Client,
+    table: AccountAddress,
+    id: u64,
+) -> anyhow::Result<ProposalSummary> {
+    let item = client
+        .get_table_item(table, "u64", PROPOSAL, id.to_string())
+        .await
+        .context(format!("no proposal found with id {}", id))?
+        .into_inner();
+    let mut p = ProposalSummary::from_json(id, &item)?;
+
+    if !p.is_resolved {
+        let state = get_gov_proposal_state(client, id).await?;
+        p.status = if can_gov_proposal_resolve(client, id).await? {
+            "resolvable"
+        } else if state == PROPOSAL_STATE_PENDING {
+            "voting"
+        } else if state == PROPOSAL_STATE_SUCCEEDED {
+            // resolution must be in another transaction than the last vote
+            "passed"
+        } else {
+            "failed"
+        }
+        .to_owned();
+    }
+    Ok(p)
+}
+
+/// The published metadata document of a proposal, and whether it matches the hash on chain.
+/// Any error is returned in the json, since the document is hosted off chain.
+pub async fn get_proposal_metadata(p: &ProposalSummary) -> Value {
+    let (Some(url), Some(onchain_hash)) = (&p.metadata_location, &p.metadata_hash) else {
+        return json!({ "error": "proposal has no metadata" });
+    };
+    if onchain_hash.is_empty() {
+        return json!({ "error": "proposal has no metadata document" });
+    }
+    match ProposalMetadata::fetch(url).await {
+        Ok((document, hash)) => json!({
+          "document": document,
+          "hash_matches": ProposalMetadata::check_hash(&hash, onchain_hash).is_ok(),
+        }),
+        Err(e) => json!({ "error": format!("{:#}", e) }),
+    }
+}
+
+/// All the proposals, oldest first.
+pub async fn get_all_proposals(client: &Client) -> anyhow::Result<Vec<ProposalSummary>> {
+    let next_id = get_next_governance_proposal_id(client).await?;
+    if next_id == 0 {
+        return Ok(vec![]);
+    }
+    let table = proposals_table(client).await?;
+    let mut list = vec![];
+    for id in 0..next_id {
+        list.push(get_proposal_from_table(client, table, id).await?);
+    }
+    Ok(list)
+}
+
+/// The votes on a proposal, from the vote events of diem_governance.
+pub async fn get_proposal_votes(client: &Client, id: u64) -> anyhow::Result<Vec<ProposalVote>> {
+    if id >= get_next_governance_proposal_id(client).await? {
+        bail!("no proposal found with id {}", id);
+    }
+    let mut votes = vec![];
+    let mut start = 0;
+    loop {
+        let page = client
+            .get_account_events(
+                AccountAddress::ONE,
+                GOVERNANCE_EVENTS,
+                "vote_events",
+                Some(start),
+                Some(EVENTS_PAGE_SIZE),
+            )
+            .await?
+            .into_inner();
+
+        for e in &page {
+            if field::<u64>(&e.data, "proposal_id")? != id {
+                continue;
+            }
+            votes.push(ProposalVote {
+                voter: field(&e.data, "voter")?,
+                num_votes: field(&e.data, "num_votes")?,
+                should_pass: e.data["should_pass"]
+                    .as_bool()
+                    .context("vote event has no should_pass")?,
+                version: e.version.into(),
+            });
+        }
+
+        if page.len() < EVENTS_PAGE_SIZE as usize {
+            break;
+        }
+        start += EVENTS_PAGE_SIZE as u64;
+    }
+    Ok(votes)
+}
+
+#[test]
+fn proposal_from_json() {
+    let v: Value = serde_json::from_str(
+        r#"{
+      "proposer": "0x87515d94a244235a1433d7117bc0cb154c613c2f4b1e67ca8d98a542ee3f59f5",
+      "execution_content": { "vec": [] },
+      "metadata": { "data": [
+        { "key": "metadata_location", "value": "0x68747470733a2f2f6578616d706c652e636f6d" },
+        { "key": "metadata_hash", "value": "0x" },
+        { "key": "RESOLVABLE_TIME_METADATA_KEY", "value": "0x0000000000000000" }
+      ] },
+      "creation_time_secs": "1700000000",
+      "execution_hash": "0xabcd",
+      "min_vote_threshold": "1000",
+      "expiration_secs": "1700086400",
+      "early_resolution_vote_threshold": { "vec": [] },
+      "yes_votes": "340282366920938463463374607431768211455",
+      "no_votes": "0",
+      "is_resolved": false,
+      "resolution_time_secs": "0"
+    }"#,
+    )
+    .unwrap();
+
+    let p = ProposalSummary::from_json(3, &v).unwrap();
+    assert_eq!(p.id, 3);
+    assert_eq!(p.metadata_location.as_deref(), Some("https://example.com"));
+    assert_eq!(p.metadata_hash.as_deref(), Some(""));
+    assert_eq!(p.yes_votes, u128::MAX);
+    assert_eq!(p.min_vote_threshold, 1000);
+    assert_eq!(p.expiration_secs, 1700086400);
+    assert_eq!(p.execution_hash, "0xabcd");
+}
@@ -1,5 +1,6 @@
 pub mod account_queries;
 pub mod chain_queries;
+pub mod gov_queries;
 pub mod query_cli;
 pub mod query_type;
 pub mod query_view;
@@ -1,11 +1,13 @@
 use std::path::PathBuf;
 
-use crate::query_type::QueryType;
+use crate::{chain_queries::get_sync_delay, query_type::QueryType};
 
 use anyhow::Result;
 use clap::Parser;
 use libra_types::{
-    core_types::app_cfg::AppCfg, exports::Client, type_extensions::client_ext::ClientExt,
+    core_types::config_layers::{ConfigOverrides, ResolvedCfg},
+    exports::Client,
+    type_extensions::client_ext::ClientExt,
 };
 use serde_json;
 use url::Url;
@@ -26,6 +28,10 @@ pub struct QueryCli {
     /// Otherwise will default to what is in the config file
     #[clap(short, long)]
     pub url: Option<Url>,
+
+    /// optional, nickname or address of a profile in the config file, to query its chain
+    #[clap(long, global = true)]
+    pub profile: Option<String>,
 }
 
 impl QueryCli {
@@ -35,17 +41,40 @@ impl QueryCli {
         // Otherwise the tool will try to fetch the libra config from the
         // usual location: ~/.libra
         // The user can set an alternative path the the config,
-        // which is useful in testnets.
+        // which is useful in testnets. The LIBRA_* environment variables
+        // apply on top of the config file.
+        let overrides = ConfigOverrides {
+            config_path: self.config_path.clone(),
+            profile: self.profile.clone(),
+            ..Default::default()
+        };
+
+        // The sync delay compares a local node against the upstream nodes
+        // of the network playlist, so it needs the config file and
+        // defaults to a node on localhost.
+        if let QueryType::SyncDelay = self.subcommand {
+            let local_url = match &self.url {
+                Some(u) => u.to_owned(),
+                None => "http://localhost:8080".parse()?,
+            };
+            // the chain of --profile or LIBRA_CHAIN_ID picks the playlist
+            let r = ResolvedCfg::load(&overrides)?;
+            let res = get_sync_delay(
+                &Client::new(local_url),
+                r.cfg.get_network_profile(Some(r.chain_id))?,
+            )
+            .await?;
+            println!("{}", serde_json::to_string_pretty(&res)?);
+            return Ok(());
+        }
 
         // Initialize client
         let client = if let Some(u) = &self.url {
             Client::new(u.clone())
-        } else if let Some(p) = &self.config_path {
-            let app_cfg = AppCfg::load(Some(p.to_owned()))?;
-            let (c, _) = Client::from_libra_config(&app_cfg, None).await?;
-            c
         } else {
-            Client::default().await?
+            let r = ResolvedCfg::load(&overrides)?;
+            let (c, _) = Client::from_libra_config(&r.cfg, Some(r.chain_id)).await?;
+            c
         };
 
         let res = self.subcommand.query_to_json(&client).await?;
@@ -1,19 +1,23 @@
 use crate::{
     account_queries::{
-        community_wallet_scheduled_transactions, community_wallet_signers,
-        get_account_balance_libra, get_events, get_transactions, get_val_config,
-        is_community_wallet_migrated,
+        community_wallet_pending_transactions, community_wallet_signers, get_account_balance_libra,
+        get_events, get_transactions, get_val_config, is_community_wallet_migrated,
+        transaction_summary, TxKind, TxsFilter,
     },
     chain_queries::{get_epoch, get_height},
+    gov_queries::{get_all_proposals, get_proposal, get_proposal_metadata, get_proposal_votes},
     query_view::get_view,
 };
 use anyhow::{bail, Context, Result};
-use diem_api_types::Transaction;
+use diem_api_types::{EntryFunctionId, Transaction};
 use diem_debugger::DiemDebugger;
+use diem_logger::prelude::*;
 use diem_sdk::{rest_client::Client, types::account_address::AccountAddress};
 use indoc::indoc;
+use libra_framework::error_map::framework_error_maps;
 use libra_types::{exports::AuthenticationKey, type_extensions::client_ext::ClientExt};
 use serde_json::json;
+use std::str::FromStr;
 
 #[derive(Debug, clap::Subcommand)]
 pub enum QueryType {
@@ -81,7 +85,7 @@ pub enum QueryType {
     },
     /// Network block height
     BlockHeight,
-    /// How far behind the local is from the upstream nodes
+    /// How far behind the local is from the upstream nodes in the network playlist
     SyncDelay,
     /// Get events
     Events {
@@ -102,11 +106,14 @@ pub enum QueryType {
         /// get transactions after this height
         txs_height: Option<u64>,
         #[clap(long)]
-        /// limit how many txs
+        /// limit how many txs, default 25
         txs_count: Option<u64>,
+        #[clap(long, value_enum)]
+        /// filter by type. Only user txs are sent by the account, the other types are from the whole chain
+        txs_type: Option<TxKind>,
         #[clap(long)]
-        /// filter by type
-        txs_type: Option<String>,
+        /// filter by entry function e.g. 0x1::ol_account::transfer
+        txs_function: Option<String>,
     },
     /// Is the community wallet migrated
     ComWalletMigrated {
@@ -125,6 +132,13 @@ pub enum QueryType {
     },
     /// Display all account structs
     Annotate { account: AccountAddress },
+    /// List the governance proposals, with their votes and status
+    Proposals,
+    /// A governance proposal, with the vote of each account and its published metadata
+    Proposal {
+        /// id of the proposal
+        id: u64,
+    },
 }
 
 impl QueryType {
@@ -187,6 +201,11 @@ impl QueryType {
                 let height = get_height(client).await?;
                 Ok(json!({ "BlockHeight": height }))
             }
+            QueryType::SyncDelay => {
+                // the sync delay needs the local node and the network playlist
+                // of the config file, which only QueryCli has
+                bail!("sync-delay needs the config file, use `libra query sync-delay`")
+            }
             QueryType::Events {
                 account,
                 withdrawn_or_deposited,
@@ -200,40 +219,56 @@ impl QueryType {
                 txs_height,
                 txs_count,
                 txs_type,
+                txs_function,
             } => {
-                let res: Vec<Transaction> = get_transactions(
-                    client,
-                    *account,
-                    *txs_height,
-                    *txs_count,
-                    txs_type.to_owned(),
-                )
-                .await?;
-                let prune_res: Vec<_> = res
-                    .iter()
-                    .map(|tx| {
-                        json!({
-                        "timestamp": tx.timestamp(),
-                        "version" : tx.version(),
-                        "is_pending" : tx.is_pending(),
-                        })
-                    })
-                    .collect();
-                Ok(json!({ "transactions": prune_res }))
+                let function = match txs_function {
+                    Some(f) => Some(
+                        EntryFunctionId::from_str(f)
+                            .context(format!("not a valid entry function id: {}", f))?,
+                    ),
+                    None => None,
+                };
+                let filter = TxsFilter {
+                    kind: *txs_type,
+                    function,
+                };
+                let res: Vec<Transaction> =
+                    get_transactions(client, *account, *txs_height, *txs_count, &filter).await?;
+                // without the maps an abort is shown by its code, in vm_status
+                let maps = framework_error_maps()
+                    .map_err(|e| warn!("cannot explain the abort codes: {:#}", e))
+                    .ok();
+                let summary: Vec<_> = res.iter().map(|t| transaction_summary(t, maps)).collect();
+                Ok(json!({ "transactions": summary }))
             }
             QueryType::ComWalletMigrated { account } => {
                 let res = is_community_wallet_migrated(client, *account).await?;
                 Ok(json!({ "migrated": res }))
             }
             QueryType::ComWalletSigners { account } => {
-                // Wont work at the moment as there is no community wallet that with governace structure
-                let _res = community_wallet_signers(client, *account).await?;
-                Ok(json!({ "signers": "None"}))
+                let res = community_wallet_signers(client, *account).await?;
+                Ok(json!({ "signers": res }))
             }
             QueryType::ComWalletPendTransactions { account } => {
-                // Wont work at the moment as there is no community wallet migrated
-                let _res = community_wallet_scheduled_transactions(client, *account).await?;
-                Ok(json!({ "pending_transactions": "None" }))
+                let res = community_wallet_pending_transactions(client, *account).await?;
+                Ok(json!({ "pending_transactions": res }))
+            }
+            QueryType::Proposals => {
+                let res = get_all_proposals(client).await?;
+                Ok(json!({ "proposals": res }))
+            }
+            QueryType::Proposal { id } => {
+                let proposal = get_proposal(client, *id).await?;
+                let metadata = get_proposal_metadata(&proposal).await;
+                let votes = get_proposal_votes(client, *id).await?;
+                let (yes, no): (Vec<_>, Vec<_>) = votes.iter().partition(|v| v.should_pass);
+                Ok(json!({
+                  "proposal": proposal,
+                  "voters_yes": yes.len(),
+                  "voters_no": no.len(),
+                  "votes": votes,
+                  "metadata": metadata,
+                }))
             }
             QueryType::Annotate { account } => {
                 let dbgger = DiemDebugger::rest_client(client.clone())?;
@@ -247,12 +282,6 @@ impl QueryType {
                 let pretty = format!("{:#}", blob.unwrap().to_string());
                 Ok(json!(pretty))
             }
-            _ => {
-                bail!(
-                    "Not implemented for type: {:?}\n Ground control to Major Tom.",
-                    self
-                )
-            }
         }
     }
 }
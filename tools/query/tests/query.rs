@@ -42,3 +42,119 @@ async fn account_annotate_test() {
     println!("{:#}", &res.as_str().unwrap());
     assert!(res.as_str().unwrap().contains("drop"));
 }
+
+/// the local node compared with itself should have no lag
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn sync_delay_test() {
+    use libra_query::chain_queries::get_sync_delay;
+    use libra_types::{core_types::network_playlist::NetworkPlaylist, exports::NamedChain};
+
+    let mut s = LibraSmoke::new(None, None)
+        .await
+        .expect("could not start swarm");
+
+    let c = s.client();
+    let np = NetworkPlaylist::new(Some(s.api_endpoint.clone()), Some(NamedChain::TESTING));
+
+    let res = get_sync_delay(&c, np).await.unwrap();
+    println!("{:#}", &res);
+    let upstream = res["upstream"].as_array().unwrap();
+    assert_eq!(upstream.len(), 1);
+    assert!(upstream[0]["is_api"].as_bool().unwrap());
+    // the upstream is checked after the local, so it can only be ahead
+    assert!(upstream[0]["version_lag"].as_i64().unwrap() >= 0);
+}
+
+/// filtering the transaction history by kind
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn txs_filter_test() {
+    use libra_query::account_queries::TxKind;
+
+    let mut s = LibraSmoke::new(None, None)
+        .await
+        .expect("could not start swarm");
+    let val_acct = s.first_account.address();
+
+    let c = s.client();
+
+    let q = QueryType::Txs {
+        account: val_acct,
+        txs_height: None,
+        txs_count: Some(5),
+        txs_type: Some(TxKind::User),
+        txs_function: None,
+    };
+    let res = q.query_to_json(&c).await.unwrap();
+    println!("{:#}", &res);
+    let txs = res["transactions"].as_array().unwrap();
+    assert!(txs.len() <= 5);
+    assert!(txs.iter().all(|t| t["type"] == "user_transaction"));
+
+    // block metadata is not sent by the account, it is read from the whole chain
+    let q = QueryType::Txs {
+        account: val_acct,
+        txs_height: None,
+        txs_count: Some(3),
+        txs_type: Some(TxKind::BlockMetadata),
+        txs_function: None,
+    };
+    let res = q.query_to_json(&c).await.unwrap();
+    let txs = res["transactions"].as_array().unwrap();
+    assert_eq!(txs.len(), 3);
+    assert!(txs
+        .iter()
+        .all(|t| t["type"] == "block_metadata_transaction"));
+    // the latest, in order
+    assert!(txs[0]["version"].as_u64() < txs[2]["version"].as_u64());
+
+    // a function nobody calls should match nothing
+    let q = QueryType::Txs {
+        account: val_acct,
+        txs_height: Some(0),
+        txs_count: None,
+        txs_type: None,
+        txs_function: Some("0x1::ol_account::not_a_function".to_string()),
+    };
+    let res = q.query_to_json(&c).await.unwrap();
+    assert!(res["transactions"].as_array().unwrap().is_empty());
+}
+
+/// a dead node at the top of the playlist should not break the client
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn upstream_failover_test() {
+    use libra_types::{
+        core_types::network_playlist::HostProfile, exports::Client,
+        type_extensions::client_ext::ClientExt,
+    };
+
+    let mut s = LibraSmoke::new(None, None)
+        .await
+        .expect("could not start swarm");
+
+    let mut cfg = s.first_account_app_cfg().unwrap();
+    let np = cfg.get_network_profile_mut(None).unwrap();
+    // nothing listens on port 1
+    np.nodes
+        .insert(0, HostProfile::new("http://127.0.0.1:1".parse().unwrap()));
+    assert_eq!(cfg.pick_url(None).unwrap().port(), Some(1));
+
+    let (c, _) = Client::from_libra_config(&cfg, None).await.unwrap();
+    assert!(c.get_index().await.is_ok());
+}
+
+/// a new chain has no governance proposals
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn proposals_test() {
+    let mut s = LibraSmoke::new(None, None)
+        .await
+        .expect("could not start swarm");
+    let c = s.client();
+
+    let res = QueryType::Proposals.query_to_json(&c).await.unwrap();
+    assert!(res["proposals"].as_array().unwrap().is_empty());
+
+    assert!(QueryType::Proposal { id: 0 }
+        .query_to_json(&c)
+        .await
+        .is_err());
+}
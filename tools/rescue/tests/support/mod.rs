@@ -158,8 +158,11 @@ pub async fn upgrade_multiple_impl(
         subcommand: Some(Governance(Propose {
             proposal_script_dir: script_dir.clone(),
             metadata_url: "http://allyourbase.com".to_string(),
+            metadata_file: None,
         })),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(smoke.encoded_pri_key.clone()),
         chain_id: Some(NamedChain::TESTING),
         config_path: Some(d.path().to_owned().join("libra-cli-config.yaml")),
@@ -167,6 +170,7 @@ pub async fn upgrade_multiple_impl(
         tx_profile: None,
         tx_cost: Some(TxCost::prod_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
         legacy_address: false,
     };
 
@@ -180,6 +184,7 @@ pub async fn upgrade_multiple_impl(
         cli.subcommand = Some(Governance(Vote {
             proposal_id: 4,
             should_fail: false,
+            skip_metadata_check: false,
         }));
         cli.test_private_key = Some(val_key.clone());
         cli.run().await.context("cli could not send vote")?;
@@ -1,8 +1,10 @@
 pub mod constants;
 pub mod generic_tx;
+pub mod offline;
 pub mod publish;
 pub mod submit_transaction;
 pub mod transfer;
+pub mod transfer_batch;
 pub mod txs_cli;
 pub mod txs_cli_community;
 pub mod txs_cli_governance;
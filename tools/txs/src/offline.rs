@@ -0,0 +1,348 @@
+//! Offline signing. A transaction is built on an online machine, signed on
+//! an air-gapped machine, and submitted from any online machine.
+//! Multi-agent transactions are signed by each signer separately, the
+//! partial signatures are exchanged as files and combined before submitting.
+//! NOTE: sponsored (fee payer) transactions are not possible, the framework's
+//! transaction_validation has no fee payer prologue. `build-unsigned --fee-payer`
+//! fails with this.
+
+use crate::submit_transaction::error_maps;
+use anyhow::{anyhow, bail, Context};
+use diem_sdk::{
+    crypto::{
+        ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+        HashValue, PrivateKey, Signature as _, SigningKey,
+    },
+    rest_client::{diem_api_types::TransactionOnChainData, Client},
+    transaction_builder::TransactionBuilder,
+    types::{
+        account_address::AccountAddress,
+        chain_id::ChainId,
+        transaction::{
+            authenticator::AccountAuthenticator, RawTransaction, RawTransactionWithData,
+            SignedTransaction, TransactionPayload,
+        },
+    },
+};
+use libra_types::{
+    core_types::{app_cfg::TxCost, cli_error::CliError},
+    exports::AuthenticationKey,
+    type_extensions::client_ext::ClientExt,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Default time window to sign and submit an offline transaction.
+pub const DEFAULT_OFFLINE_EXPIRY_SECS: u64 = 60 * 60;
+
+/// An unsigned transaction file. The raw transaction is the only thing
+/// which gets signed, the other fields are for people to read.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnsignedTx {
+    pub sender: AccountAddress,
+    pub sequence_number: u64,
+    /// what the transaction calls
+    pub payload: PayloadSummary,
+    pub chain_id: u8,
+    pub expiration_timestamp_secs: u64,
+    pub max_gas_amount: u64,
+    pub gas_unit_price: u64,
+    /// BCS encoded RawTransaction, as hex
+    pub raw_txn: String,
+    /// other accounts which must sign a multi-agent transaction
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secondary_signers: Vec<AccountAddress>,
+}
+
+impl UnsignedTx {
+    pub fn new(raw: &RawTransaction) -> anyhow::Result<Self> {
+        Ok(Self {
+            sender: raw.sender(),
+            sequence_number: raw.sequence_number(),
+            payload: PayloadSummary::new(raw.payload()),
+            chain_id: raw.chain_id().id(),
+            expiration_timestamp_secs: raw.expiration_timestamp_secs(),
+            max_gas_amount: raw.max_gas_amount(),
+            gas_unit_price: raw.gas_unit_price(),
+            raw_txn: hex::encode(bcs::to_bytes(raw)?),
+            secondary_signers: vec![],
+        })
+    }
+
+    pub fn is_multi_agent(&self) -> bool {
+        !self.secondary_signers.is_empty()
+    }
+
+    /// what every signer of a multi-agent transaction signs
+    fn multi_agent_message(&self) -> anyhow::Result<RawTransactionWithData> {
+        Ok(RawTransactionWithData::new_multi_agent(
+            self.raw_transaction()?,
+            self.secondary_signers.clone(),
+        ))
+    }
+
+    /// Decodes the raw transaction. The readable fields must agree with it,
+    /// so that what the signer reviews is what gets signed.
+    pub fn raw_transaction(&self) -> anyhow::Result<RawTransaction> {
+        let bytes = hex::decode(&self.raw_txn).context("raw_txn is not valid hex")?;
+        let raw: RawTransaction =
+            bcs::from_bytes(&bytes).context("raw_txn is not a BCS RawTransaction")?;
+        if Self::new(&raw)?.summary_differs(self) {
+            bail!("the fields of the unsigned transaction file do not match raw_txn, it may have been tampered with");
+        }
+        Ok(raw)
+    }
+
+    fn summary_differs(&self, other: &Self) -> bool {
+        self.sender != other.sender
+            || self.sequence_number != other.sequence_number
+            || self.payload != other.payload
+            || self.chain_id != other.chain_id
+            || self.expiration_timestamp_secs != other.expiration_timestamp_secs
+            || self.max_gas_amount != other.max_gas_amount
+            || self.gas_unit_price != other.gas_unit_price
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?).context(format!(
+            "could not write unsigned transaction to {:?}",
+            path
+        ))
+    }
+
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let s = fs::read_to_string(path)
+            .context(format!("could not read unsigned transaction at {:?}", path))?;
+        serde_json::from_str(&s).map_err(|e| {
+            CliError::bad_input(format!("invalid unsigned transaction file: {}", e)).into()
+        })
+    }
+}
+
+/// The call of a transaction, decoded for the signer to review. The
+/// arguments are BCS encoded, as hex.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayloadSummary {
+    /// <ADDRESS>::<MODULE>::<FUNCTION>, or the hash of a script
+    pub function_id: String,
+    pub type_args: Vec<String>,
+    pub args: Vec<String>,
+}
+
+impl PayloadSummary {
+    pub fn new(payload: &TransactionPayload) -> Self {
+        match payload {
+            TransactionPayload::EntryFunction(f) => Self {
+                function_id: format!("{}::{}", f.module().short_str_lossless(), f.function()),
+                type_args: f.ty_args().iter().map(|t| t.to_string()).collect(),
+                args: f
+                    .args()
+                    .iter()
+                    .map(|a| format!("0x{}", hex::encode(a)))
+                    .collect(),
+            },
+            TransactionPayload::Script(s) => Self {
+                function_id: format!(
+                    "script {}",
+                    HashValue::sha3_256_of(s.code()).to_hex_literal()
+                ),
+                type_args: s.ty_args().iter().map(|t| t.to_string()).collect(),
+                args: s.args().iter().map(|a| format!("{:?}", a)).collect(),
+            },
+            _ => Self {
+                function_id: "unsupported payload".to_owned(),
+                type_args: vec![],
+                args: vec![],
+            },
+        }
+    }
+}
+
+/// Online step: builds the raw transaction with the sender's current
+/// sequence number and the chain id of the node.
+pub async fn build_unsigned(
+    client: &Client,
+    sender: AccountAddress,
+    secondary_signers: Vec<AccountAddress>,
+    payload: TransactionPayload,
+    tx_cost: &TxCost,
+    expiry_secs: u64,
+) -> anyhow::Result<UnsignedTx> {
+    let index = client.get_index().await?.into_inner();
+    let chain_id = ChainId::new(index.chain_id);
+    let sequence_number = client.get_sequence_number(sender).await?;
+
+    let expiration = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() + expiry_secs;
+
+    let raw = TransactionBuilder::new(payload, expiration, chain_id)
+        .sender(sender)
+        .sequence_number(sequence_number)
+        .gas_unit_price(tx_cost.coin_price_per_unit)
+        .max_gas_amount(tx_cost.max_gas_unit_for_tx)
+        .build();
+
+    let mut unsigned = UnsignedTx::new(&raw)?;
+    unsigned.secondary_signers = secondary_signers;
+    Ok(unsigned)
+}
+
+/// Offline step: signs the raw transaction. Does not touch the network.
+pub fn sign_unsigned(
+    unsigned: &UnsignedTx,
+    key: &Ed25519PrivateKey,
+) -> anyhow::Result<SignedTransaction> {
+    if unsigned.is_multi_agent() {
+        bail!("this is a multi-agent transaction, each signer makes a partial signature");
+    }
+    let raw = unsigned.raw_transaction()?;
+    check_expiry(&raw)?;
+
+    let signed = raw
+        .sign(key, key.public_key())
+        .context("could not sign transaction")?
+        .into_inner();
+    Ok(signed)
+}
+
+fn check_expiry(raw: &RawTransaction) -> anyhow::Result<()> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    if raw.expiration_timestamp_secs() < now {
+        bail!(CliError::bad_input(
+            "the unsigned transaction has expired, build it again"
+        ));
+    }
+    Ok(())
+}
+
+/// One signer's signature of a multi-agent transaction, exchanged as a
+/// file between the co-signers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialSignature {
+    pub signer: AccountAddress,
+    pub public_key: Ed25519PublicKey,
+    pub signature: Ed25519Signature,
+}
+
+impl PartialSignature {
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .context(format!("could not write partial signature to {:?}", path))
+    }
+
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let s = fs::read_to_string(path)
+            .context(format!("could not read partial signature at {:?}", path))?;
+        serde_json::from_str(&s).map_err(|e| {
+            CliError::bad_input(format!("invalid partial signature file: {}", e)).into()
+        })
+    }
+}
+
+/// Offline step of a multi-agent transaction: one of the signers signs.
+/// The signer's address is needed since a rotated key does not derive it.
+pub fn sign_partial(
+    unsigned: &UnsignedTx,
+    key: &Ed25519PrivateKey,
+    signer: AccountAddress,
+) -> anyhow::Result<PartialSignature> {
+    if !unsigned.is_multi_agent() {
+        bail!("not a multi-agent transaction, sign it directly");
+    }
+    if signer != unsigned.sender && !unsigned.secondary_signers.contains(&signer) {
+        bail!(CliError::bad_input(format!(
+            "{} is not a signer of this transaction",
+            signer
+        )));
+    }
+    check_expiry(&unsigned.raw_transaction()?)?;
+
+    let message = unsigned.multi_agent_message()?;
+    let signature = key
+        .sign(&message)
+        .map_err(|e| anyhow!("could not sign transaction: {:?}", e))?;
+
+    Ok(PartialSignature {
+        signer,
+        public_key: key.public_key(),
+        signature,
+    })
+}
+
+/// Combines the partial signatures of the sender and all the secondary
+/// signers into a signed multi-agent transaction.
+pub fn combine_partials(
+    unsigned: &UnsignedTx,
+    partials: &[PartialSignature],
+) -> anyhow::Result<SignedTransaction> {
+    let message = unsigned.multi_agent_message()?;
+
+    let authenticator = |addr: AccountAddress| -> anyhow::Result<AccountAuthenticator> {
+        let p = partials
+            .iter()
+            .find(|p| p.signer == addr)
+            .ok_or_else(|| CliError::bad_input(format!("missing the signature of {}", addr)))?;
+        p.signature
+            .verify(&message, &p.public_key)
+            .map_err(|e| CliError::bad_input(format!("invalid signature from {}: {}", addr, e)))?;
+        Ok(AccountAuthenticator::ed25519(
+            p.public_key.clone(),
+            p.signature.clone(),
+        ))
+    };
+
+    let sender = authenticator(unsigned.sender)?;
+    let secondary = unsigned
+        .secondary_signers
+        .iter()
+        .map(|a| authenticator(*a))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(SignedTransaction::new_multi_agent(
+        unsigned.raw_transaction()?,
+        sender,
+        unsigned.secondary_signers.clone(),
+        secondary,
+    ))
+}
+
+/// The auth key of the signing key, so the signer can check it is the
+/// account's current key (it may differ from the address if rotated).
+pub fn signer_auth_key(key: &Ed25519PrivateKey) -> AuthenticationKey {
+    AuthenticationKey::ed25519(&key.public_key())
+}
+
+pub fn save_signed(signed: &SignedTransaction, path: &Path) -> anyhow::Result<()> {
+    fs::write(path, bcs::to_bytes(signed)?)
+        .context(format!("could not write signed transaction to {:?}", path))
+}
+
+pub fn read_signed(path: &Path) -> anyhow::Result<SignedTransaction> {
+    let bytes =
+        fs::read(path).context(format!("could not read signed transaction at {:?}", path))?;
+    bcs::from_bytes(&bytes).map_err(|e| {
+        CliError::bad_input(format!("not a BCS signed transaction file: {}", e)).into()
+    })
+}
+
+/// Broadcast step: submits the signed transaction and waits for it.
+pub async fn submit_signed(
+    client: &Client,
+    signed: &SignedTransaction,
+) -> anyhow::Result<TransactionOnChainData> {
+    let pending = client.submit(signed).await?.into_inner();
+    println!("transaction sent, hash: {}", pending.hash);
+
+    let res = client
+        .wait_for_transaction_bcs(&pending)
+        .await?
+        .into_inner();
+    let status = res.info.status();
+    if !status.is_success() {
+        return Err(CliError::vm_abort(status, error_maps()).into());
+    }
+    Ok(res)
+}
@@ -7,8 +7,9 @@ use diem_logger::prelude::*;
 use diem_sdk::{
     crypto::{HashValue, PrivateKey},
     rest_client::{
-        diem_api_types::{TransactionOnChainData, UserTransaction},
-        Client,
+        diem_api_types::{DiemError, DiemErrorCode, TransactionOnChainData, UserTransaction},
+        error::RestError,
+        Client, DiemErrorResponse,
     },
     transaction_builder::TransactionBuilder,
     types::{
@@ -17,8 +18,13 @@ use diem_sdk::{
         AccountKey, LocalAccount,
     },
 };
+use libra_framework::error_map::framework_error_maps;
 use libra_types::{
-    core_types::app_cfg::{AppCfg, TxCost},
+    core_types::{
+        app_cfg::{AppCfg, AutoGas, TxCost, CONFIG_FILE_NAME, MINIMUM_GAS_PRICE_IN_DIEM},
+        cli_error::{CliError, MoveAbort},
+        error_map::ErrorMaps,
+    },
     exports::{AuthenticationKey, Ed25519PrivateKey},
     ol_progress::OLProgress,
     type_extensions::{
@@ -36,6 +42,8 @@ use url::Url;
 pub struct Sender {
     pub local_account: LocalAccount,
     pub tx_cost: TxCost,
+    /// if set, the cost of each transaction is estimated instead of tx_cost
+    pub auto_gas: Option<AutoGas>,
     client: Client,
     chain_id: ChainId,
     pub response: Option<TransactionOnChainData>,
@@ -73,6 +81,7 @@ impl Sender {
         Ok(Self {
             client,
             tx_cost: TxCost::default_baseline_cost(),
+            auto_gas: None,
             local_account,
             chain_id,
             response: None,
@@ -84,24 +93,37 @@ impl Sender {
         cost.clone_into(&mut self.tx_cost);
     }
 
+    /// Estimate the cost of each transaction, instead of the fixed tx_cost.
+    pub fn set_auto_gas(&mut self, auto_gas: Option<&AutoGas>) {
+        self.auto_gas = auto_gas.cloned();
+    }
+
     /// load from local app configs
-    pub async fn from_app_cfg(app_cfg: &AppCfg, profile: Option<String>) -> anyhow::Result<Self> {
-        let profile = app_cfg.get_profile(profile)?;
+    pub async fn from_app_cfg(app_cfg: &AppCfg, nickname: Option<String>) -> anyhow::Result<Self> {
+        let profile = app_cfg.get_profile(nickname.clone())?;
 
         let key = match profile.borrow_private_key() {
             Ok(k) => k.to_owned(),
-            _ => {
-                let leg_keys = libra_wallet::account_keys::get_keys_from_prompt()?;
-                leg_keys.child_0_owner.pri_key
-            }
+            // the config is where save_file writes it, in node_home
+            _ => match libra_wallet::keystore::unlock_profile_key(
+                &app_cfg.workspace.node_home.join(CONFIG_FILE_NAME),
+                app_cfg,
+                nickname,
+            )? {
+                Some(k) => k,
+                None => libra_wallet::account_keys::get_key_at_index_from_prompt(
+                    "",
+                    profile.derivation_index.unwrap_or(0),
+                )?,
+            },
         };
 
         let temp_seq_num = 0;
 
         // Create authentication key and initialize client with URL
         let auth_key = AuthenticationKey::ed25519(&key.public_key());
-        let url = &app_cfg.pick_url(None)?;
-        let client = Client::new(url.clone());
+        // uses the preferred node, or fails over to another node in the playlist
+        let (client, chain_id) = Client::from_libra_config(app_cfg, None).await?;
         let address = client
             .lookup_originating_address(auth_key)
             .await
@@ -110,22 +132,16 @@ impl Sender {
         let mut local_account = LocalAccount::new(address, key, temp_seq_num);
         let seq_num = local_account.sequence_number_mut();
 
-        // check if we can connect to this client, or exit
-        let chain_id = match client.get_index().await {
-            Ok(metadata) => {
-                // update sequence number
-                *seq_num = client
-                    .get_sequence_number(address)
-                    .await
-                    .context("failed to get sequence number")?;
-                ChainId::new(metadata.into_inner().chain_id)
-            }
-            Err(_) => bail!("cannot connect to client at {:?}", &url),
-        };
+        // update sequence number
+        *seq_num = client
+            .get_sequence_number(address)
+            .await
+            .context("failed to get sequence number")?;
 
         let s = Sender {
             client,
             tx_cost: app_cfg.tx_configs.get_cost(None),
+            auto_gas: app_cfg.tx_configs.auto_gas.clone(),
             local_account,
             chain_id,
             response: None,
@@ -182,6 +198,7 @@ impl Sender {
             let s = Sender {
                 client,
                 tx_cost: TxCost::default_baseline_cost(),
+                auto_gas: None,
                 local_account,
                 chain_id,
                 response: None,
@@ -205,31 +222,146 @@ impl Sender {
             info!("script code hash: {}", &hash.to_hex_literal());
         }
 
-        let signed = self.sign_payload(payload);
+        let Some(auto) = self.auto_gas.clone() else {
+            let signed = self.sign_payload(payload);
+            return self.submit_and_check(&signed).await;
+        };
+
+        self.tx_cost = self.estimate_tx_cost(payload.clone(), &auto).await?;
+        let signed = self.sign_payload(payload.clone());
+        match self.submit_and_check(&signed).await {
+            Err(e) if auto.retry_expired && self.expired_uncommitted(&e, &signed).await => {
+                let prioritized = self
+                    .client
+                    .estimate_gas_price()
+                    .await
+                    .ok()
+                    .and_then(|r| r.into_inner().prioritized_gas_estimate);
+                let price = AutoGas::retry_price(self.tx_cost.coin_price_per_unit, prioritized);
+                println!(
+                    "transaction expired in the mempool, sending it again at gas price {}",
+                    price
+                );
+                self.tx_cost.coin_price_per_unit = price;
+                // the expired transaction did not use its sequence number
+                *self.local_account.sequence_number_mut() = signed.sequence_number();
+                let signed = self.sign_payload(payload);
+                self.submit_and_check(&signed).await
+            }
+            r => r,
+        }
+    }
+
+    /// The cost of a transaction from a simulation, with the margin of
+    /// auto_gas on the max gas, and the gas price estimate of the node.
+    pub async fn estimate_tx_cost(
+        &mut self,
+        payload: TransactionPayload,
+        auto: &AutoGas,
+    ) -> anyhow::Result<TxCost> {
+        // the simulation signs, so it takes a sequence number
+        let seq = self.local_account.sequence_number();
+        let sim = self.estimate(payload).await;
+        *self.local_account.sequence_number_mut() = seq;
+
+        let sim = sim?;
+        let tx = sim
+            .first()
+            .context("the simulation returned no transaction")?;
+        self.tx_cost_from_simulation(tx, auto).await
+    }
+
+    /// The auto_gas cost of a transaction which was already simulated.
+    pub async fn tx_cost_from_simulation(
+        &self,
+        tx: &UserTransaction,
+        auto: &AutoGas,
+    ) -> anyhow::Result<TxCost> {
+        if !tx.info.success {
+            bail!("the transaction would fail: {}", tx.info.vm_status);
+        }
+        let price = self
+            .client
+            .estimate_gas_price()
+            .await
+            .context("could not get the gas price estimate of the node")?
+            .into_inner()
+            .gas_estimate;
+        let cost = TxCost {
+            max_gas_unit_for_tx: auto.max_gas(tx.info.gas_used.into()),
+            coin_price_per_unit: price.max(MINIMUM_GAS_PRICE_IN_DIEM),
+            user_tx_timeout: self.tx_cost.user_tx_timeout,
+        };
+        info!(
+            "estimated gas: {} units at price {}",
+            cost.max_gas_unit_for_tx, cost.coin_price_per_unit
+        );
+        Ok(cost)
+    }
+
+    /// Signs and submits a multi-agent transaction, where the secondary
+    /// signers also sign the payload. Used by scripts which take more than
+    /// one signer.
+    pub async fn sign_submit_wait_multi_agent(
+        &mut self,
+        payload: TransactionPayload,
+        secondary_signers: Vec<&LocalAccount>,
+    ) -> anyhow::Result<TransactionOnChainData> {
+        let signed = self.sign_multi_agent_payload(payload, secondary_signers);
+        self.submit_and_check(&signed).await
+    }
+
+    /// submit a signed transaction, and error if it did not succeed
+    async fn submit_and_check(
+        &mut self,
+        signed: &SignedTransaction,
+    ) -> anyhow::Result<TransactionOnChainData> {
         let spin = OLProgress::spin_steady(500, "awaiting transaction response".to_string());
         println!("sending transaction...");
-        let r = self.submit(&signed).await?;
+        let r = self.submit(signed).await?;
         println!("transaction sent");
         self.response = Some(r.clone());
         spin.finish_and_clear();
         debug!("{:?}", &r);
+
+        // the transaction was committed, but may have aborted
+        let status = r.info.status();
+        if !status.is_success() {
+            return Err(CliError::vm_abort(status, error_maps()).into());
+        }
         OLProgress::complete("transaction success");
         Ok(r)
     }
 
     /// Signs a transaction payload.
     pub fn sign_payload(&mut self, payload: TransactionPayload) -> SignedTransaction {
+        let tb = self.transaction_builder(payload);
+        self.local_account.sign_with_transaction_builder(tb)
+    }
+
+    /// Signs a multi-agent transaction payload with the sender and all the secondary signers.
+    /// NOTE: sponsored (fee payer) transactions are not possible, the
+    /// framework's transaction_validation has no fee payer prologue.
+    pub fn sign_multi_agent_payload(
+        &mut self,
+        payload: TransactionPayload,
+        secondary_signers: Vec<&LocalAccount>,
+    ) -> SignedTransaction {
+        let tb = self.transaction_builder(payload);
+        self.local_account
+            .sign_multi_agent_with_transaction_builder(secondary_signers, tb)
+    }
+
+    fn transaction_builder(&self, payload: TransactionPayload) -> TransactionBuilder {
         let t = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
             .as_secs();
         let time = t + (DEFAULT_TIMEOUT_SECS * 10);
 
-        let tb = TransactionBuilder::new(payload, time, self.chain_id)
+        TransactionBuilder::new(payload, time, self.chain_id)
             .gas_unit_price(self.tx_cost.coin_price_per_unit)
-            .max_gas_amount(self.tx_cost.max_gas_unit_for_tx);
-
-        self.local_account.sign_with_transaction_builder(tb)
+            .max_gas_amount(self.tx_cost.max_gas_unit_for_tx)
     }
 
     /// submit to API and wait for the transaction on chain data
@@ -260,6 +392,9 @@ impl Sender {
             true => Ok(status.to_owned()),
             false => {
                 println!("transaction not successful, status: {:?}", &status);
+                if let Some(why) = MoveAbort::from_status(status, error_maps()).explain() {
+                    println!("reason: {}", why);
+                }
                 Err(status.to_owned())
             }
         }
@@ -292,4 +427,46 @@ impl Sender {
     pub fn client(&self) -> &Client {
         &self.client
     }
+
+    /// A transaction which expired in the mempool before it was committed,
+    /// e.g. its gas price was too low. Only then is its sequence number unused,
+    /// so it can be sent again. An abort is a committed transaction, whatever
+    /// its reason says.
+    async fn expired_uncommitted(&self, e: &anyhow::Error, signed: &SignedTransaction) -> bool {
+        // the client reports the expiry while waiting, aborts are a CliError
+        let client_expiry = e
+            .downcast_ref::<RestError>()
+            .is_some_and(|r| r.to_string().to_lowercase().contains("expired"));
+        if !client_expiry {
+            return false;
+        }
+        matches!(
+            self.client
+                .get_transaction_by_hash(signed.clone().committed_hash())
+                .await,
+            Err(r) if is_not_found(&r)
+        )
+    }
+}
+
+/// the node does not know the transaction, it was never committed
+pub(crate) fn is_not_found(e: &RestError) -> bool {
+    matches!(
+        e,
+        RestError::Api(DiemErrorResponse {
+            error: DiemError {
+                error_code: DiemErrorCode::TransactionNotFound,
+                ..
+            },
+            ..
+        })
+    )
+}
+
+/// The framework error maps, to explain an abort. Without them the abort is
+/// still reported, with its code only.
+pub(crate) fn error_maps() -> Option<&'static ErrorMaps> {
+    framework_error_maps()
+        .map_err(|e| warn!("cannot explain the abort code: {:#}", e))
+        .ok()
 }
@@ -1,6 +1,6 @@
 //! form a transfer payload and execute transaction
 use super::submit_transaction::Sender;
-use anyhow::bail;
+use anyhow::Context;
 use diem_sdk::{
     rest_client::diem_api_types::TransactionOnChainData, types::account_address::AccountAddress,
 };
@@ -30,17 +30,22 @@ impl Sender {
             println!("will succeed: {success}");
             let gas = res[0].info.gas_used;
             println!("gas used: {gas}");
+
+            // price the simulation above, instead of simulating again
+            if let Some(auto) = self.auto_gas.clone() {
+                let cost = self.tx_cost_from_simulation(&res[0], &auto).await?;
+                println!(
+                    "auto gas: max gas {}, gas price {}",
+                    cost.max_gas_unit_for_tx, cost.coin_price_per_unit
+                );
+            }
             Ok(None)
         } else {
-            match self.sign_submit_wait(payload).await {
-                Ok(tx) => Ok(Some(tx)),
-                Err(e) => {
-                    bail!(
-                        "ERROR: transaction could not complete, message: {}",
-                        e.to_string()
-                    )
-                }
-            }
+            let tx = self
+                .sign_submit_wait(payload)
+                .await
+                .context("transaction could not complete")?;
+            Ok(Some(tx))
         }
     }
 }
@@ -0,0 +1,476 @@
+//! Transfers to many recipients from a CSV or JSON file.
+//! Progress is written to a results file before anything is broadcast, so
+//! that a batch interrupted by a crash can be resumed without paying twice.
+
+use crate::submit_transaction::{is_not_found, Sender};
+use anyhow::{bail, Context};
+use diem_sdk::{
+    crypto::HashValue,
+    rest_client::{diem_api_types::Transaction, error::RestError},
+    types::{account_address::AccountAddress, transaction::SignedTransaction},
+};
+use libra_cached_packages::libra_stdlib;
+use libra_query::account_queries::get_account_balance_libra;
+use libra_types::{
+    core_types::cli_error::CliError, move_resource::gas_coin,
+    type_extensions::client_ext::ClientExt,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// How many transfers are in flight at once, each with the next sequence number.
+pub const DEFAULT_PIPELINE_SIZE: usize = 20;
+
+#[derive(clap::Args)]
+/// Transfer coins to many accounts, listed in a CSV or JSON file.
+/// CSV columns: recipient,amount,note (note is optional). Run it again with the
+/// same file to resume an interrupted batch.
+pub struct TransferBatchTx {
+    #[clap(short, long)]
+    /// CSV file, or JSON file (.json) with a list of {recipient, amount, note}
+    pub file: PathBuf,
+    #[clap(short, long)]
+    /// Results file, defaults to <file>.results.json next to the input
+    pub out: Option<PathBuf>,
+    #[clap(long, default_value_t = DEFAULT_PIPELINE_SIZE)]
+    /// How many transactions to submit before waiting for them
+    pub pipeline: usize,
+    #[clap(long)]
+    /// Only validate the file, do not send anything
+    pub check: bool,
+}
+
+/// A row of the payments file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Payment {
+    pub recipient: String,
+    /// in coins, with decimals
+    pub amount: f64,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentStatus {
+    /// not signed yet
+    Unsent,
+    /// signed and maybe broadcast, the outcome is not known yet
+    Submitted,
+    Success,
+    /// committed and aborted, or rejected by the node
+    Failed,
+    /// the sequence number was used but the transaction cannot be found, check by hand
+    Unknown,
+}
+
+/// The progress of one row. Recorded before the transaction is broadcast.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentResult {
+    pub row: usize,
+    pub recipient: AccountAddress,
+    pub amount: f64,
+    #[serde(default)]
+    pub note: Option<String>,
+    pub status: PaymentStatus,
+    #[serde(default)]
+    pub hash: Option<HashValue>,
+    #[serde(default)]
+    pub sequence_number: Option<u64>,
+    #[serde(default)]
+    pub expiration_timestamp_secs: Option<u64>,
+    /// BCS of the signed transaction, as hex. Resending the same bytes
+    /// cannot pay twice.
+    #[serde(default)]
+    pub signed_txn: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl PaymentResult {
+    fn reset(&mut self) {
+        self.status = PaymentStatus::Unsent;
+        self.hash = None;
+        self.sequence_number = None;
+        self.expiration_timestamp_secs = None;
+        self.signed_txn = None;
+        self.error = None;
+    }
+
+    fn record_signed(&mut self, signed: &SignedTransaction) -> anyhow::Result<()> {
+        self.status = PaymentStatus::Submitted;
+        self.hash = Some(signed.clone().committed_hash());
+        self.sequence_number = Some(signed.sequence_number());
+        self.expiration_timestamp_secs = Some(signed.expiration_timestamp_secs());
+        self.signed_txn = Some(hex::encode(bcs::to_bytes(signed)?));
+        self.error = None;
+        Ok(())
+    }
+
+    fn signed_transaction(&self) -> anyhow::Result<SignedTransaction> {
+        let hex_str = self
+            .signed_txn
+            .as_ref()
+            .context("no signed transaction recorded")?;
+        Ok(bcs::from_bytes(&hex::decode(hex_str)?)?)
+    }
+
+    fn record_outcome(&mut self, success: bool, vm_status: String) {
+        if success {
+            self.status = PaymentStatus::Success;
+            self.error = None;
+        } else {
+            self.status = PaymentStatus::Failed;
+            self.error = Some(vm_status);
+        }
+    }
+}
+
+/// Reads the payments from a JSON file (by extension) or a CSV file with headers.
+pub fn read_payments(path: &Path) -> anyhow::Result<Vec<Payment>> {
+    let is_json = path.extension().map(|e| e == "json").unwrap_or(false);
+    if is_json {
+        let data =
+            fs::read_to_string(path).context(format!("could not read file {}", path.display()))?;
+        return serde_json::from_str(&data).map_err(|e| {
+            CliError::bad_input(format!("invalid payments file {}: {}", path.display(), e)).into()
+        });
+    }
+
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_path(path)
+        .context(format!("could not read file {}", path.display()))?;
+    rdr.deserialize()
+        .enumerate()
+        .map(|(i, r)| r.map_err(|e| CliError::bad_input(format!("row {}: {}", i + 1, e)).into()))
+        .collect()
+}
+
+/// Checks every row before anything is sent. All the problems are reported at once.
+pub fn validate_payments(
+    payments: &[Payment],
+    sender: AccountAddress,
+) -> anyhow::Result<Vec<PaymentResult>> {
+    let mut errors = vec![];
+    let mut list = vec![];
+
+    for (i, p) in payments.iter().enumerate() {
+        let row = i + 1;
+        let recipient = match p.recipient.parse::<AccountAddress>() {
+            Ok(a) => a,
+            Err(e) => {
+                errors.push(format!(
+                    "row {}: invalid recipient {}: {}",
+                    row, p.recipient, e
+                ));
+                continue;
+            }
+        };
+        if recipient == sender {
+            errors.push(format!("row {}: the recipient is the sender", row));
+        }
+        if !p.amount.is_finite() || gas_coin::cast_decimal_to_coin(p.amount) == 0 {
+            errors.push(format!("row {}: invalid amount {}", row, p.amount));
+        }
+        list.push(PaymentResult {
+            row,
+            recipient,
+            amount: p.amount,
+            note: p.note.clone(),
+            status: PaymentStatus::Unsent,
+            hash: None,
+            sequence_number: None,
+            expiration_timestamp_secs: None,
+            signed_txn: None,
+            error: None,
+        });
+    }
+
+    if !errors.is_empty() {
+        bail!(CliError::bad_input(format!(
+            "the payments file has errors, nothing was sent:\n{}",
+            errors.join("\n")
+        )));
+    }
+    Ok(list)
+}
+
+/// Takes the progress of a previous run, if the rows still match the input.
+fn merge_previous(
+    mut list: Vec<PaymentResult>,
+    previous: Vec<PaymentResult>,
+) -> anyhow::Result<Vec<PaymentResult>> {
+    for old in previous {
+        let i = old.row.checked_sub(1).context("rows start at 1")?;
+        let matches = list.get(i).map(|new| {
+            new.recipient == old.recipient
+                && gas_coin::cast_decimal_to_coin(new.amount)
+                    == gas_coin::cast_decimal_to_coin(old.amount)
+        });
+        if matches != Some(true) {
+            bail!(CliError::bad_input(format!(
+                "row {} of the payments file changed since the last run, use a new results file",
+                old.row
+            )));
+        }
+        list[i] = old;
+    }
+    Ok(list)
+}
+
+fn save_results(list: &[PaymentResult], path: &Path) -> anyhow::Result<()> {
+    fs::write(path, serde_json::to_string_pretty(list)?)
+        .context(format!("could not write results to {}", path.display()))
+}
+
+impl TransferBatchTx {
+    fn results_path(&self) -> PathBuf {
+        self.out
+            .clone()
+            .unwrap_or_else(|| self.file.with_extension("results.json"))
+    }
+
+    pub async fn run(&self, sender: &mut Sender) -> anyhow::Result<()> {
+        let payments = read_payments(&self.file)?;
+        let sender_addr = sender.local_account.address();
+        let list = validate_payments(&payments, sender_addr)?;
+
+        let out = self.results_path();
+        let mut list = if out.exists() {
+            let data = fs::read_to_string(&out)?;
+            let previous: Vec<PaymentResult> = serde_json::from_str(&data)
+                .context(format!("could not parse results file {}", out.display()))?;
+            println!("resuming from {}", out.display());
+            merge_previous(list, previous)?
+        } else {
+            list
+        };
+
+        let to_pay: u64 = list
+            .iter()
+            .filter(|r| r.status == PaymentStatus::Unsent)
+            .map(|r| gas_coin::cast_decimal_to_coin(r.amount))
+            .sum();
+        let balance = get_account_balance_libra(sender.client(), sender_addr).await?;
+        if to_pay > balance.unlocked {
+            bail!(CliError::bad_input(format!(
+                "the batch needs {} coins but the unlocked balance is {}",
+                to_pay, balance.unlocked
+            )));
+        }
+
+        if self.check {
+            println!("SUCCESS: {} payments are valid", list.len());
+            return Ok(());
+        }
+
+        self.resume_submitted(sender, &mut list, &out).await?;
+        self.send_unsent(sender, &mut list, &out).await?;
+
+        let count = |s: PaymentStatus| list.iter().filter(|r| r.status == s).count();
+        println!(
+            "paid: {}, failed: {}, unknown: {}, not sent: {}. Results in {}",
+            count(PaymentStatus::Success),
+            count(PaymentStatus::Failed),
+            count(PaymentStatus::Unknown),
+            count(PaymentStatus::Unsent) + count(PaymentStatus::Submitted),
+            out.display()
+        );
+        if list.iter().any(|r| r.status != PaymentStatus::Success) {
+            bail!("not all payments were completed, see {}", out.display());
+        }
+        Ok(())
+    }
+
+    /// Finds out what happened to the transactions of an interrupted run.
+    /// Only a transaction which expired, and whose sequence number is still
+    /// unused, is sent again.
+    async fn resume_submitted(
+        &self,
+        sender: &mut Sender,
+        list: &mut [PaymentResult],
+        out: &Path,
+    ) -> anyhow::Result<()> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let client = sender.client().clone();
+
+        for r in list
+            .iter_mut()
+            .filter(|r| r.status == PaymentStatus::Submitted)
+        {
+            let signed = r.signed_transaction()?;
+            let expired = r.expiration_timestamp_secs.unwrap_or_default() <= now;
+            if !expired {
+                // the same bytes have the same hash, at most one executes
+                let _ = client.submit(&signed).await;
+                if let Ok(res) = client.wait_for_signed_transaction_bcs(&signed).await {
+                    let status = res.into_inner().info.status().to_owned();
+                    r.record_outcome(status.is_success(), format!("{:?}", status));
+                    continue;
+                }
+            }
+
+            match client
+                .get_transaction_by_hash(r.hash.context("no hash recorded")?)
+                .await
+            {
+                Ok(res) => match res.into_inner() {
+                    Transaction::PendingTransaction(_) => {
+                        println!("row {}: still pending, run again later", r.row);
+                    }
+                    tx => r.record_outcome(tx.success(), tx.vm_status()),
+                },
+                Err(e) if is_not_found(&e) && !expired => {
+                    println!("row {}: not found yet, run again after it expires", r.row);
+                }
+                Err(e) if is_not_found(&e) => {
+                    let seq = client
+                        .get_sequence_number(sender.local_account.address())
+                        .await?;
+                    if seq <= r.sequence_number.unwrap_or_default() {
+                        // expired without its sequence number being used
+                        r.reset();
+                    } else {
+                        r.status = PaymentStatus::Unknown;
+                        r.error = Some(
+                            "transaction not found, but its sequence number was used".to_owned(),
+                        );
+                    }
+                }
+                Err(e) => bail!(e),
+            }
+        }
+        save_results(list, out)
+    }
+
+    /// Signs and submits the unsent rows, a pipeline at a time. Every
+    /// transaction is recorded before it is broadcast.
+    async fn send_unsent(
+        &self,
+        sender: &mut Sender,
+        list: &mut [PaymentResult],
+        out: &Path,
+    ) -> anyhow::Result<()> {
+        let client = sender.client().clone();
+        let pipeline = self.pipeline.max(1);
+
+        loop {
+            let rows: Vec<usize> = list
+                .iter()
+                .enumerate()
+                .filter(|(_, r)| r.status == PaymentStatus::Unsent)
+                .map(|(i, _)| i)
+                .take(pipeline)
+                .collect();
+            if rows.is_empty() {
+                return Ok(());
+            }
+
+            // start from the chain's sequence number, in case a previous pipeline broke off
+            let seq = client
+                .get_sequence_number(sender.local_account.address())
+                .await?;
+            *sender.local_account.sequence_number_mut() = seq;
+
+            for &i in &rows {
+                let payload = libra_stdlib::ol_account_transfer(
+                    list[i].recipient,
+                    gas_coin::cast_decimal_to_coin(list[i].amount),
+                );
+                let signed = sender.sign_payload(payload);
+                list[i].record_signed(&signed)?;
+            }
+            save_results(list, out)?;
+
+            let mut sent = vec![];
+            for &i in &rows {
+                let signed = list[i].signed_transaction()?;
+                match client.submit(&signed).await {
+                    Ok(_) => {
+                        println!("row {}: sent {}", list[i].row, list[i].recipient);
+                        sent.push((i, signed));
+                    }
+                    Err(RestError::Api(e)) => {
+                        // the node answered, so this and the rest of the pipeline were not accepted
+                        list[i].reset();
+                        list[i].status = PaymentStatus::Failed;
+                        list[i].error = Some(e.to_string());
+                        rows.iter()
+                            .filter(|&&j| j > i)
+                            .for_each(|&j| list[j].reset());
+                        break;
+                    }
+                    Err(e) => {
+                        // it may have been received, resuming will find out
+                        save_results(list, out)?;
+                        bail!(
+                            "could not submit row {}, run again to resume: {}",
+                            list[i].row,
+                            e
+                        );
+                    }
+                }
+            }
+
+            for (i, signed) in sent {
+                match client.wait_for_signed_transaction_bcs(&signed).await {
+                    Ok(res) => {
+                        let status = res.into_inner().info.status().to_owned();
+                        list[i].record_outcome(status.is_success(), format!("{:?}", status));
+                    }
+                    Err(e) => {
+                        save_results(list, out)?;
+                        bail!(
+                            "could not confirm row {}, run again to resume: {}",
+                            list[i].row,
+                            e
+                        );
+                    }
+                }
+            }
+            save_results(list, out)?;
+        }
+    }
+}
+
+#[test]
+fn validate_and_merge_payments() {
+    let d = diem_temppath::TempPath::new();
+    d.create_as_dir().unwrap();
+    let file = d.path().join("payments.csv");
+    fs::write(
+        &file,
+        "recipient, amount, note\n0xabc, 1.5, rent\n0x123, 2,\nnot-an-address, 1,\n0x1, 0,\n",
+    )
+    .unwrap();
+
+    let payments = read_payments(&file).unwrap();
+    assert_eq!(payments.len(), 4);
+    assert_eq!(payments[0].note.as_deref(), Some("rent"));
+
+    // all the errors are reported, nothing is sent
+    let e = validate_payments(&payments, AccountAddress::ONE).unwrap_err();
+    let msg = e.to_string();
+    assert!(msg.contains("row 3"));
+    assert!(msg.contains("row 4"));
+
+    let list = validate_payments(&payments[..2], AccountAddress::ONE).unwrap();
+    assert_eq!(list[1].row, 2);
+
+    // the progress of a previous run is kept
+    let mut previous = list.clone();
+    previous[0].status = PaymentStatus::Success;
+    let merged = merge_previous(list.clone(), previous.clone()).unwrap();
+    assert_eq!(merged[0].status, PaymentStatus::Success);
+    assert_eq!(merged[1].status, PaymentStatus::Unsent);
+
+    // but not if the file was edited in between
+    previous[0].amount = 3.0;
+    assert!(merge_previous(list, previous).is_err());
+}
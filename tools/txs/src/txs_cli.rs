@@ -1,22 +1,41 @@
 use crate::{
-    publish::encode_publish_payload, submit_transaction::Sender, txs_cli_community::CommunityTxs,
-    txs_cli_governance::GovernanceTxs, txs_cli_user::UserTxs, txs_cli_vals::ValidatorTxs,
+    generic_tx::build_entry_function,
+    offline::{
+        build_unsigned, combine_partials, read_signed, save_signed, sign_partial, sign_unsigned,
+        signer_auth_key, submit_signed, PartialSignature, UnsignedTx, DEFAULT_OFFLINE_EXPIRY_SECS,
+    },
+    publish::encode_publish_payload,
+    submit_transaction::Sender,
+    transfer_batch::TransferBatchTx,
+    txs_cli_community::CommunityTxs,
+    txs_cli_governance::GovernanceTxs,
+    txs_cli_user::UserTxs,
+    txs_cli_vals::ValidatorTxs,
 };
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Parser;
 use diem::common::types::MovePackageDir;
 use diem_sdk::{
     crypto::{ed25519::Ed25519PrivateKey, ValidCryptoMaterialStringExt},
     rest_client::Client,
-    types::{account_address::AccountAddress, AccountKey},
+    types::{account_address::AccountAddress, transaction::TransactionPayload, AccountKey},
 };
 use indoc::indoc;
 use libra_types::{
-    core_types::app_cfg::{AppCfg, TxCost, TxType},
+    core_types::{
+        app_cfg::{AppCfg, TxCost, TxType},
+        cli_error::CliError,
+        config_layers::{ConfigOverrides, ResolvedCfg},
+    },
     exports::{ChainId, NamedChain},
+    type_extensions::client_ext::ClientExt,
+};
+use libra_wallet::{
+    account_keys::{get_key_at_index_from_mnem, get_key_at_index_from_prompt},
+    keystore::unlock_profile_key,
+    load_keys,
 };
-use libra_wallet::account_keys::{get_keys_from_mnem, get_keys_from_prompt};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use url::Url;
 
 #[derive(Parser)]
@@ -30,10 +49,19 @@ pub struct TxsCli {
     #[clap(short, long)]
     pub config_path: Option<PathBuf>,
 
+    /// optional, nickname or address of the profile in libra-cli-config.yaml to sign with,
+    /// which also picks its chain. Otherwise the default profile
+    #[clap(long, global = true)]
+    pub profile: Option<String>,
+
     /// optional, mnemonic to pass at runtime. Otherwise this will prompt for mnemonic
     #[clap(short, long)]
     pub mnemonic: Option<String>,
 
+    /// optional, prompt for the mnemonic passphrase, if the account was derived with one
+    #[clap(long)]
+    pub passphrase: bool,
+
     /// optional, Private key of the account. Otherwise this will prompt for mnemonic. Warning: intended for testing
     #[clap(short, long)]
     pub test_private_key: Option<String>,
@@ -61,6 +89,15 @@ pub struct TxsCli {
     #[clap(long)]
     pub estimate_only: bool,
 
+    /// optional, estimate the gas of the transaction and the gas price from the node,
+    /// instead of the costs of the tx profile. See auto_gas in libra-cli-config.yaml.
+    /// Is mutually exclusive with --tx-profile and the --tx-cost flags
+    #[clap(
+        long,
+        conflicts_with_all = ["tx_profile", "max_gas_unit_for_tx", "coin_price_per_unit", "user_tx_timeout"]
+    )]
+    pub auto_gas: bool,
+
     /// optional, use legacy (v5) 16-byte address format for a sender
     #[clap(long)]
     pub legacy_address: bool,
@@ -91,6 +128,70 @@ pub enum TxsSub {
         #[clap(short, long)]
         amount: f64,
     },
+    /// Transfer coins to many accounts from a CSV or JSON file, resumable
+    TransferBatch(TransferBatchTx),
+    /// Build an unsigned transaction file calling an entry function, to be signed offline.
+    /// Needs a connection to a node, but no keys.
+    /// e.g. a transfer: --function-id 0x1::ol_account::transfer --args '0xabc, 1000000'
+    BuildUnsigned {
+        /// the account which will sign and send the transaction
+        #[clap(short, long)]
+        sender: AccountAddress,
+        /// entry function identifier <ADDRESS>::<MODULE_ID>::<FUNCTION_NAME>
+        #[clap(short, long)]
+        function_id: String,
+        /// type arguments separated by commas
+        #[clap(short, long)]
+        type_args: Option<String>,
+        /// function arguments separated by commas
+        #[clap(short, long)]
+        args: Option<String>,
+        /// optional, other accounts which also sign, for a multi-agent transaction. Comma separated
+        #[clap(long, value_delimiter = ',')]
+        secondary_signers: Vec<AccountAddress>,
+        /// NOT SUPPORTED: sponsored (fee payer) transactions. The framework has no
+        /// fee payer prologue, so the sender always pays the gas
+        #[clap(long)]
+        fee_payer: Option<AccountAddress>,
+        /// seconds until the transaction expires. Leave time to sign it offline
+        #[clap(long, default_value_t = DEFAULT_OFFLINE_EXPIRY_SECS)]
+        expiry_secs: u64,
+        /// path to save the unsigned transaction file
+        #[clap(short, long)]
+        output: PathBuf,
+    },
+    /// Sign an unsigned transaction file using the key options of `libra txs`. Works offline.
+    /// For a multi-agent transaction this saves a partial signature, to combine with the others.
+    SignUnsigned {
+        /// path of the unsigned transaction file
+        #[clap(short, long)]
+        file: PathBuf,
+        /// path to save the signed transaction (BCS), or the partial signature
+        #[clap(short, long)]
+        output: PathBuf,
+        /// optional, the account this key signs for in a multi-agent transaction.
+        /// Defaults to the address derived from the key, which differs if the key was rotated
+        #[clap(long)]
+        signer: Option<AccountAddress>,
+    },
+    /// Combine the partial signatures of all signers of a multi-agent transaction. Works offline.
+    CombineSigned {
+        /// path of the unsigned transaction file
+        #[clap(short, long)]
+        file: PathBuf,
+        /// paths of the partial signature files, comma separated
+        #[clap(short, long, value_delimiter = ',', required = true)]
+        partials: Vec<PathBuf>,
+        /// path to save the signed transaction (BCS)
+        #[clap(short, long)]
+        output: PathBuf,
+    },
+    /// Submit a signed transaction file (BCS), and wait for it to execute
+    SubmitSigned {
+        /// path of the signed transaction file
+        #[clap(short, long)]
+        file: PathBuf,
+    },
     #[clap(hide(true))]
     /// Warn: Publishing contracts is for testing purposes only on Testnet
     Publish(MovePackageDir),
@@ -138,36 +239,140 @@ pub enum TxsSub {
 impl TxsCli {
     /// Executes the transaction CLI command based on parsed arguments.
     pub async fn run(&self) -> Result<()> {
-        // Load application configuration
-        let app_cfg = AppCfg::load(self.config_path.clone())?;
-        let profile = app_cfg.get_profile(None)?;
+        // Offline signing happens on a machine which may have neither
+        // a config file nor a network connection
+        match &self.subcommand {
+            Some(TxsSub::BuildUnsigned {
+                fee_payer: Some(_), ..
+            }) => {
+                return Err(CliError::bad_input(
+                    "sponsored (fee payer) transactions are not supported, the framework has no fee payer prologue. The sender pays the gas",
+                )
+                .into());
+            }
+            Some(TxsSub::SignUnsigned {
+                file,
+                output,
+                signer,
+            }) => {
+                let r = ResolvedCfg::load_if_exists(&self.config_overrides())?;
+                let pri_key = self.get_private_key(
+                    r.as_ref().map(|r| &r.cfg),
+                    r.as_ref().and_then(|r| r.config_path.as_deref()),
+                )?;
 
-        // Determine private key based on CLI options or prompts
-        let pri_key = if let Some(pk) = &self.test_private_key {
-            println!("using private key from cli args --test-private-key");
-            Ed25519PrivateKey::from_encoded_string(pk)?
-        } else if let Some(m) = &self.mnemonic {
-            println!("using private key from cli args --mnemonic");
-            let legacy = get_keys_from_mnem(m.to_string())?;
-            legacy.child_0_owner.pri_key
-        } else if let Ok(p) = profile.borrow_private_key() {
-            println!("use private key from test libra-cli-config.yaml");
-            p.to_owned()
-        } else {
-            let legacy = get_keys_from_prompt()?;
-            legacy.child_0_owner.pri_key
-        };
+                let unsigned = UnsignedTx::read(file)?;
+                println!("{}", serde_json::to_string_pretty(&unsigned)?);
+                let auth_key = signer_auth_key(&pri_key);
+                println!("signing with auth key: {}", auth_key);
 
-        // Determine chain ID and URL for client
-        let chain_name = self.chain_id.unwrap_or(app_cfg.workspace.default_chain_id);
-        let url = if let Some(u) = self.url.as_ref() {
-            u.to_owned()
+                if unsigned.is_multi_agent() {
+                    let signer = signer.unwrap_or(auth_key.account_address());
+                    let partial = sign_partial(&unsigned, &pri_key, signer)?;
+                    partial.save(output)?;
+                    println!(
+                        "SUCCESS: partial signature of {} saved to {}",
+                        signer,
+                        output.display()
+                    );
+                } else {
+                    let signed = sign_unsigned(&unsigned, &pri_key)?;
+                    save_signed(&signed, output)?;
+                    println!("SUCCESS: signed transaction saved to {}", output.display());
+                }
+                return Ok(());
+            }
+            Some(TxsSub::CombineSigned {
+                file,
+                partials,
+                output,
+            }) => {
+                let unsigned = UnsignedTx::read(file)?;
+                let partials = partials
+                    .iter()
+                    .map(|p| PartialSignature::read(p))
+                    .collect::<Result<Vec<_>>>()?;
+                let signed = combine_partials(&unsigned, &partials)?;
+                save_signed(&signed, output)?;
+                println!("SUCCESS: signed transaction saved to {}", output.display());
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        // Load application configuration, with the LIBRA_* environment
+        // variables and the flags on top. The profile may be on another chain
+        let ResolvedCfg {
+            cfg: app_cfg,
+            chain_id: chain_name,
+            config_path,
+            ..
+        } = ResolvedCfg::load(&self.config_overrides())?;
+        // Initialize client, failing over to another node in the playlist
+        // if the preferred one is down
+        let client = if let Some(u) = self.url.as_ref() {
+            Client::new(u.to_owned())
         } else {
-            app_cfg.pick_url(Some(chain_name))?
+            let (c, _) = Client::from_libra_config(&app_cfg, Some(chain_name)).await?;
+            c
         };
 
-        // Initialize client
-        let client = Client::new(url);
+        // Handle mutually exclusive options for transaction cost
+        if self.tx_cost.is_some() && self.tx_profile.is_some() {
+            println!("ERROR: --tx-cost and --tx-profile are mutually exclusive. Either set the costs explicitly or choose a profile in libra-cli-config.yaml, exiting");
+        }
+
+        // Determine transaction cost, a --tx-cost is already applied to every profile
+        let tx_cost = app_cfg.tx_configs.get_cost(self.tx_profile.clone());
+
+        // These need a node, but not the keys
+        match &self.subcommand {
+            Some(TxsSub::BuildUnsigned {
+                sender,
+                function_id,
+                type_args,
+                args,
+                secondary_signers,
+                expiry_secs,
+                output,
+                ..
+            }) => {
+                let payload = TransactionPayload::EntryFunction(build_entry_function(
+                    function_id,
+                    type_args,
+                    args,
+                )?);
+                let unsigned = build_unsigned(
+                    &client,
+                    *sender,
+                    secondary_signers.to_owned(),
+                    payload,
+                    &tx_cost,
+                    *expiry_secs,
+                )
+                .await?;
+                unsigned.save(output)?;
+                println!("{}", serde_json::to_string_pretty(&unsigned)?);
+                println!(
+                    "SUCCESS: unsigned transaction saved to {}",
+                    output.display()
+                );
+                return Ok(());
+            }
+            Some(TxsSub::SubmitSigned { file }) => {
+                let signed = read_signed(file)?;
+                let res = submit_signed(&client, &signed).await?;
+                println!(
+                    "SUCCESS: transaction executed, hash: {}",
+                    res.info.transaction_hash()
+                );
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        // Determine private key based on CLI options or prompts
+        let pri_key = self.get_private_key(Some(&app_cfg), config_path.as_deref())?;
 
         // Initialize sender
         let mut send = Sender::new(
@@ -178,19 +383,11 @@ impl TxsCli {
         )
         .await?;
 
-        // Handle mutually exclusive options for transaction cost
-        if self.tx_cost.is_some() && self.tx_profile.is_some() {
-            println!("ERROR: --tx-cost and --tx-profile are mutually exclusive. Either set the costs explicitly or choose a profile in libra-cli-config.yaml, exiting");
-        }
-
-        // Determine transaction cost
-        let tx_cost = self
-            .tx_cost
-            .clone()
-            .unwrap_or_else(|| app_cfg.tx_configs.get_cost(self.tx_profile.clone()));
-
-        // Set transaction cost for sender
+        // Set transaction cost for sender, explicit costs are not estimated
         send.set_tx_cost(&tx_cost);
+        if self.tx_cost.is_none() {
+            send.set_auto_gas(app_cfg.tx_configs.auto_gas.as_ref());
+        }
 
         // Execute subcommand based on parsed input
         match &self.subcommand {
@@ -199,6 +396,14 @@ impl TxsCli {
                     .await?;
                 Ok(())
             }
+            Some(TxsSub::TransferBatch(batch)) => {
+                batch
+                    .run(&mut send)
+                    .await
+                    .context("could not complete the batch transfer")?;
+                println!("SUCCESS: batch transfer completed");
+                Ok(())
+            }
             Some(TxsSub::Publish(move_opts)) => {
                 let payload = encode_publish_payload(move_opts)?;
                 send.sign_submit_wait(payload).await?;
@@ -226,6 +431,65 @@ All that's left is an unhappy ending\"
             }
         }
     }
+
+    /// The config values set by the flags, to apply on top of the config file
+    fn config_overrides(&self) -> ConfigOverrides {
+        ConfigOverrides {
+            config_path: self.config_path.clone(),
+            chain_id: self.chain_id,
+            profile: self.profile.clone(),
+            upstream_urls: self.url.iter().cloned().collect(),
+            auto_gas: self.auto_gas.then_some(true),
+            ..Default::default()
+        }
+        .with_tx_cost(self.tx_cost.as_ref())
+    }
+
+    /// Determine private key based on CLI options, the config file, the keystore, or prompts
+    fn get_private_key(
+        &self,
+        app_cfg: Option<&AppCfg>,
+        config_file: Option<&Path>,
+    ) -> Result<Ed25519PrivateKey> {
+        // a requested profile must exist, the default one is optional
+        let profile = match app_cfg {
+            Some(a) if self.profile.is_some() => Some(a.get_profile(self.profile.clone())?),
+            Some(a) => a.get_profile(None).ok(),
+            None => None,
+        };
+        let profile_key = profile.and_then(|p| p.borrow_private_key().ok());
+        // accounts other than the owner are at another child number of the mnemonic
+        let index = profile.and_then(|p| p.derivation_index).unwrap_or(0);
+        let passphrase = || -> Result<String> {
+            if self.passphrase {
+                load_keys::prompt_passphrase(false)
+            } else {
+                Ok(String::new())
+            }
+        };
+
+        let pri_key = if let Some(pk) = &self.test_private_key {
+            println!("using private key from cli args --test-private-key");
+            Ed25519PrivateKey::from_encoded_string(pk)?
+        } else if let Some(m) = &self.mnemonic {
+            println!("using private key from cli args --mnemonic");
+            get_key_at_index_from_mnem(m.to_string(), &passphrase()?, index)?
+        } else if let Some(p) = profile_key {
+            println!("use private key from test libra-cli-config.yaml");
+            p.to_owned()
+        } else if let Some(k) = app_cfg
+            .zip(config_file)
+            .map(|(a, f)| unlock_profile_key(f, a, self.profile.clone()))
+            .transpose()?
+            .flatten()
+        {
+            println!("using private key from the encrypted keystore");
+            k
+        } else {
+            get_key_at_index_from_prompt(&passphrase()?, index)?
+        };
+        Ok(pri_key)
+    }
 }
 
 /// Converts an account address to a legacy format (v5).
@@ -239,3 +503,20 @@ pub fn to_legacy_address(address: &AccountAddress) -> anyhow::Result<AccountAddr
 
     AccountAddress::from_hex_literal(&format!("0x{}", address_str)).map_err(|e| anyhow::anyhow!(e))
 }
+
+#[test]
+fn auto_gas_conflicts_with_explicit_costs() {
+    let parse = |args: &[&str]| TxsCli::try_parse_from([&["txs"][..], args].concat());
+    assert!(parse(&["--auto-gas"]).is_ok());
+    assert!(parse(&[
+        "--auto-gas",
+        "--max-gas-unit-for-tx",
+        "1000",
+        "--coin-price-per-unit",
+        "100",
+        "--user-tx-timeout",
+        "60"
+    ])
+    .is_err());
+    assert!(parse(&["--auto-gas", "--tx-profile", "baseline"]).is_err());
+}
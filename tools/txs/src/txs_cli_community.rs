@@ -1,6 +1,7 @@
 //! Validator subcommands
 
 use crate::submit_transaction::Sender;
+use anyhow::Context;
 use diem_types::account_address::AccountAddress;
 use libra_cached_packages::libra_stdlib;
 use libra_query::{account_queries, query_view};
@@ -36,73 +37,57 @@ pub enum CommunityTxs {
 impl CommunityTxs {
     pub async fn run(&self, sender: &mut Sender) -> anyhow::Result<()> {
         match &self {
-            CommunityTxs::GovInit(init) => match init.run(sender).await {
-                Ok(_) => println!("SUCCESS: community wallet initialized"),
-                Err(e) => {
-                    println!(
-                        "ERROR: could not initialize Community Wallet, message: {}",
-                        e
-                    );
-                }
-            },
-            CommunityTxs::GovOffer(offer) => match offer.run(sender).await {
-                Ok(_) => println!("SUCCESS: community wallet offer proposed"),
-                Err(e) => {
-                    println!("ERROR: could not propose offer, message: {}", e);
-                }
-            },
-            CommunityTxs::GovClaim(claim) => match claim.run(sender).await {
-                Ok(_) => println!("SUCCESS: community wallet offer claimed"),
-                Err(e) => {
-                    println!("ERROR: could not claim offer, message: {}", e);
-                }
-            },
-            CommunityTxs::GovCage(cage) => match cage.run(sender).await {
-                Ok(_) => println!("SUCCESS: community wallet finalized"),
-                Err(e) => {
-                    println!("ERROR: could not finalize wallet, message: {}", e);
-                }
-            },
-            CommunityTxs::GovAdmin(admin) => match admin.run(sender).await {
-                Ok(_) => println!("SUCCESS: community wallet admin proposed"),
-                Err(e) => {
-                    println!("ERROR: could not propose new admin, message: {}", e);
-                }
-            },
-            CommunityTxs::Propose(propose) => match propose.run(sender).await {
-                Ok(_) => println!("SUCCESS: community wallet transfer proposed"),
-                Err(e) => {
-                    println!("ERROR: community wallet transfer rejected, message: {}", e);
-                }
-            },
-            CommunityTxs::Veto(veto) => match veto.run(sender).await {
-                Ok(_) => println!("SUCCESS: veto vote submitted"),
-                Err(e) => {
-                    println!("ERROR: veto vote rejected, message: {}", e);
-                }
-            },
-            CommunityTxs::Batch(batch) => match batch.run(sender).await {
-                Ok(_) => {}
-                Err(e) => {
-                    println!("ERROR: could not add admin, message: {}", e);
-                }
-            },
-            CommunityTxs::Migration(migration) => match migration.run(sender).await {
-                Ok(_) => {}
-                Err(e) => {
-                    println!("ERROR: could not migrate, message: {}", e);
-                }
-            },
+            CommunityTxs::GovInit(init) => {
+                init.run(sender)
+                    .await
+                    .context("could not initialize Community Wallet")?;
+                println!("SUCCESS: community wallet initialized");
+            }
+            CommunityTxs::GovOffer(offer) => {
+                offer.run(sender).await.context("could not propose offer")?;
+                println!("SUCCESS: community wallet offer proposed");
+            }
+            CommunityTxs::GovClaim(claim) => {
+                claim.run(sender).await.context("could not claim offer")?;
+                println!("SUCCESS: community wallet offer claimed");
+            }
+            CommunityTxs::GovCage(cage) => {
+                cage.run(sender)
+                    .await
+                    .context("could not finalize wallet")?;
+                println!("SUCCESS: community wallet finalized");
+            }
+            CommunityTxs::GovAdmin(admin) => {
+                admin
+                    .run(sender)
+                    .await
+                    .context("could not propose new admin")?;
+                println!("SUCCESS: community wallet admin proposed");
+            }
+            CommunityTxs::Propose(propose) => {
+                propose
+                    .run(sender)
+                    .await
+                    .context("community wallet transfer rejected")?;
+                println!("SUCCESS: community wallet transfer proposed");
+            }
+            CommunityTxs::Veto(veto) => {
+                veto.run(sender).await.context("veto vote rejected")?;
+                println!("SUCCESS: veto vote submitted");
+            }
+            CommunityTxs::Batch(batch) => {
+                batch.run(sender).await.context("could not execute batch")?;
+            }
+            CommunityTxs::Migration(migration) => {
+                migration.run(sender).await.context("could not migrate")?;
+            }
             // for tests only - TODO Remove when migration is finished
-            CommunityTxs::GovInitDeprectated => match self.run_init_deprecated(sender).await {
-                Ok(_) => println!("SUCCESS: community wallet initialized"),
-                Err(e) => {
-                    println!(
-                        "ERROR: could not initialize Community Wallet, message: {}",
-                        e
-                    );
-                }
-            },
+            CommunityTxs::GovInitDeprectated => {
+                self.run_init_deprecated(sender)
+                    .await
+                    .context("could not initialize Community Wallet")?;
+                println!("SUCCESS: community wallet initialized");
+            }
         }
 
         Ok(())
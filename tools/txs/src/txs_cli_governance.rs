@@ -8,7 +8,18 @@ use libra_cached_packages::{
     libra_stdlib,
     libra_stdlib::{diem_governance_ol_create_proposal_v2, diem_governance_ol_vote},
 };
-use std::{fs, path::PathBuf};
+use libra_framework::builder::framework_generate_upgrade_proposal::read_script_steps;
+use libra_query::{
+    chain_queries::{
+        can_gov_proposal_resolve, get_gov_proposal_approved_hash, is_gov_proposal_resolved,
+    },
+    gov_queries,
+};
+use libra_types::core_types::proposal_metadata::ProposalMetadata;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 #[derive(clap::Subcommand)]
 pub enum GovernanceTxs {
@@ -21,6 +32,10 @@ pub enum GovernanceTxs {
         #[clap(short, long)]
         /// URL which describes the proposal
         metadata_url: String,
+
+        #[clap(long)]
+        /// Path to the proposal metadata document, which must be published at the metadata url. Defaults to the proposal_metadata.json generated by `framework upgrade`
+        metadata_file: Option<PathBuf>,
     },
     /// Vote for a chain upgrade
     Vote {
@@ -32,6 +47,10 @@ pub enum GovernanceTxs {
         /// Must explicitly inform if it should fail (default is to pass)
         // In the absense of this flag it assumes you are voting "should pass"
         should_fail: bool,
+
+        #[clap(long)]
+        /// Vote even if the published metadata of the proposal cannot be fetched or does not match the hash on chain
+        skip_metadata_check: bool,
     },
     /// Resolve by sending all compiled bytes of the transaction
     // All proposals need to be resolved by any user submitting the actual bytes in a transaction. This transaction has it's hash registered in the proposal, so that only the actual bytes of the script can be submitted, and any user is able to do so. This assumes that the proposal passed.
@@ -43,6 +62,16 @@ pub enum GovernanceTxs {
         /// Path to the directory of the compiled proposal script
         proposal_script_dir: PathBuf,
     },
+    /// Resolve all the steps of a multi-step upgrade, in order
+    // The steps are the script dirs written by `framework upgrade`, e.g. 1-move-stdlib, 2-vendor-stdlib, 3-libra-framework. Steps already resolved are skipped, so this can be run again after a failure.
+    ResolveAll {
+        #[clap(short = 'i', long)]
+        /// On-chain ID of the proposal
+        proposal_id: u64,
+        #[clap(short = 'd', long)]
+        /// Path to the directory with the compiled script of each step
+        upgrade_dir: PathBuf,
+    },
     /// Tickle the epoch boundary
     EpochBoundary,
 }
@@ -53,6 +82,7 @@ impl GovernanceTxs {
             GovernanceTxs::Propose {
                 proposal_script_dir,
                 metadata_url,
+                metadata_file,
             } => {
                 let hash_path = proposal_script_dir.join("script_sha3");
                 if !proposal_script_dir.exists() || !hash_path.exists() {
@@ -63,6 +93,26 @@ impl GovernanceTxs {
                 }
                 let hash = fs::read_to_string(&hash_path)?;
 
+                let metadata_hash = match metadata_file
+                    .clone()
+                    .or_else(|| ProposalMetadata::find(proposal_script_dir))
+                {
+                    Some(path) => {
+                        let (meta, meta_hash) = ProposalMetadata::read(&path)?;
+                        println!("proposal metadata:\n{}", meta.display());
+                        println!(
+                            "the file {} must be published unchanged at {}",
+                            path.display(),
+                            metadata_url
+                        );
+                        meta_hash.to_hex()
+                    }
+                    None => {
+                        println!("WARN: no proposal metadata document found, voters will not be able to check the proposal");
+                        String::new()
+                    }
+                };
+
                 let num =
                     libra_query::chain_queries::get_next_governance_proposal_id(sender.client())
                         .await?;
@@ -75,24 +125,30 @@ impl GovernanceTxs {
                 diem_governance_ol_create_proposal_v2(
                     hex::decode(hash)?,
                     metadata_url.as_bytes().to_vec(),
-                    "metadata struct".to_string().as_bytes().to_vec(), // TODO
+                    metadata_hash.as_bytes().to_vec(),
                     true,
                 )
             }
             GovernanceTxs::Vote {
                 proposal_id,
                 should_fail,
-            } => diem_governance_ol_vote(*proposal_id, !*should_fail), // NOTE: the default is to vote for "pass" so we invert the BOOL versus what diem_governance.move expects.
+                skip_metadata_check,
+            } => {
+                if let Err(e) = check_proposal_metadata(sender, *proposal_id).await {
+                    if !*skip_metadata_check {
+                        bail!("{:#}\nuse --skip-metadata-check to vote anyways", e);
+                    }
+                    println!("WARN: {:#}", e);
+                }
+                diem_governance_ol_vote(*proposal_id, !*should_fail) // NOTE: the default is to vote for "pass" so we invert the BOOL versus what diem_governance.move expects.
+            }
             GovernanceTxs::Resolve {
                 proposal_id,
                 proposal_script_dir,
             } => {
-                if libra_query::chain_queries::is_gov_proposal_resolved(
-                    sender.client(),
-                    *proposal_id,
-                )
-                .await
-                .context("cannot get status of proposal")?
+                if is_gov_proposal_resolved(sender.client(), *proposal_id)
+                    .await
+                    .context("cannot get status of proposal")?
                 {
                     bail!("proposal {} has already been resolved", proposal_id);
                 }
@@ -102,18 +158,12 @@ impl GovernanceTxs {
                     "proposal script cannot be found at {proposal_script_dir:?}"
                 );
 
-                let proposal_bytes = std::fs::read(proposal_script_dir.join("script.mv")).unwrap();
-
-                let proposal_script = Script::new(
-                    proposal_bytes,
-                    vec![],
-                    vec![
-                        TransactionArgument::U64(*proposal_id), // proposal_id.into()
-                    ],
-                );
-
-                TransactionPayload::Script(proposal_script)
+                resolve_payload(*proposal_id, proposal_script_dir)?
             }
+            GovernanceTxs::ResolveAll {
+                proposal_id,
+                upgrade_dir,
+            } => return resolve_all(sender, *proposal_id, upgrade_dir).await,
             GovernanceTxs::EpochBoundary => libra_stdlib::diem_governance_trigger_epoch(),
         };
 
@@ -121,3 +171,111 @@ impl GovernanceTxs {
         Ok(())
     }
 }
+
+/// show the voter the published metadata of the proposal, and check it is the one on chain
+async fn check_proposal_metadata(sender: &Sender, proposal_id: u64) -> anyhow::Result<()> {
+    let proposal = gov_queries::get_proposal(sender.client(), proposal_id).await?;
+    let onchain_hash = proposal.metadata_hash.unwrap_or_default();
+    if onchain_hash.is_empty() {
+        println!("proposal {} has no metadata document", proposal_id);
+        return Ok(());
+    }
+    let url = proposal
+        .metadata_location
+        .context("proposal has no metadata location")?;
+    let (meta, hash) = ProposalMetadata::fetch(&url).await?;
+    ProposalMetadata::check_hash(&hash, &onchain_hash)?;
+    println!("proposal {} metadata:\n{}", proposal_id, meta.display());
+    Ok(())
+}
+
+/// the transaction which resolves a step of a proposal with its compiled script
+fn resolve_payload(proposal_id: u64, script_dir: &Path) -> anyhow::Result<TransactionPayload> {
+    let path = script_dir.join("script.mv");
+    let proposal_bytes =
+        fs::read(&path).context(format!("cannot read proposal script at {:?}", path))?;
+
+    let proposal_script = Script::new(
+        proposal_bytes,
+        vec![],
+        vec![
+            TransactionArgument::U64(proposal_id), // proposal_id.into()
+        ],
+    );
+
+    Ok(TransactionPayload::Script(proposal_script))
+}
+
+/// resolve each step of the proposal in order, stopping at the first which
+/// cannot be resolved or aborts
+async fn resolve_all(
+    sender: &mut Sender,
+    proposal_id: u64,
+    upgrade_dir: &Path,
+) -> anyhow::Result<()> {
+    // the same steps `framework upgrade` wrote into the proposal metadata
+    let steps = read_script_steps(upgrade_dir)?;
+    let names: Vec<&str> = steps.iter().map(|s| s.name.as_str()).collect();
+    println!("found upgrade steps: {}", names.join(", "));
+
+    if is_gov_proposal_resolved(sender.client(), proposal_id)
+        .await
+        .context("cannot get status of proposal")?
+    {
+        bail!("proposal {} has already been resolved", proposal_id);
+    }
+
+    // the approved hash is the script of the next step, so earlier steps were resolved already
+    let approved = get_gov_proposal_approved_hash(sender.client(), proposal_id).await?;
+    let Some(start) = steps.iter().position(|s| s.script_hash == approved) else {
+        bail!(
+            "none of the scripts in {:?} is the next step of proposal {}, which has the approved hash {:?}",
+            upgrade_dir,
+            proposal_id,
+            approved
+        );
+    };
+    if start > 0 {
+        println!("already resolved: {}", names[..start].join(", "));
+    }
+
+    let report = |i: usize, reason: String| {
+        anyhow::anyhow!(
+            "stopped at step {}: {}\nresolved: [{}], not resolved: [{}]",
+            names[i],
+            reason,
+            names[..i].join(", "),
+            names[i..].join(", ")
+        )
+    };
+
+    for (i, step) in steps.iter().enumerate().skip(start) {
+        if !can_gov_proposal_resolve(sender.client(), proposal_id).await? {
+            return Err(report(i, "the proposal cannot be resolved now".to_owned()));
+        }
+        let approved = get_gov_proposal_approved_hash(sender.client(), proposal_id).await?;
+        if step.script_hash != approved {
+            return Err(report(
+                i,
+                format!(
+                    "script hash {} is not the approved hash {}",
+                    step.script_hash, approved
+                ),
+            ));
+        }
+
+        println!("resolving step {} of {}: {}", i + 1, steps.len(), step.name);
+        let payload = resolve_payload(proposal_id, &upgrade_dir.join(&step.name))?;
+        if let Err(e) = sender.sign_submit_wait(payload).await {
+            return Err(report(i, format!("transaction failed, {:#}", e)));
+        }
+        println!("SUCCESS: resolved step {}", step.name);
+    }
+
+    println!(
+        "SUCCESS: all {} steps of proposal {} are resolved",
+        steps.len(),
+        proposal_id
+    );
+    Ok(())
+}
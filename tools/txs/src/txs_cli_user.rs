@@ -1,6 +1,7 @@
 //! Validator subcommands
 
 use crate::submit_transaction::Sender;
+use anyhow::Context;
 use dialoguer::Confirm;
 use diem::common::types::RotationProofChallenge;
 use diem_sdk::{
@@ -29,28 +30,25 @@ pub enum UserTxs {
 impl UserTxs {
     pub async fn run(&self, sender: &mut Sender) -> anyhow::Result<()> {
         match &self {
-            UserTxs::RotateKey(rotate) => match rotate.run(sender, true).await {
-                Ok(_) => println!("SUCCESS: private key rotated"),
-                Err(e) => {
-                    println!("ERROR: could not rotate private key, message: {}", e);
-                }
-            },
-            UserTxs::SetSlow(slow) => match slow.run(sender).await {
-                Ok(_) => println!("SUCCESS: account set to Slow Wallet"),
-                Err(e) => {
-                    println!(
-                        "ERROR: could set the account to Slow Wallet, message: {}",
-                        e
-                    );
-                }
-            },
+            UserTxs::RotateKey(rotate) => {
+                rotate
+                    .run(sender, true)
+                    .await
+                    .context("could not rotate private key")?;
+                println!("SUCCESS: private key rotated");
+            }
+            UserTxs::SetSlow(slow) => {
+                slow.run(sender)
+                    .await
+                    .context("could not set the account to Slow Wallet")?;
+                println!("SUCCESS: account set to Slow Wallet");
+            }
             UserTxs::RotationCapability(offer_rotation_capability) => {
-                match offer_rotation_capability.run(sender).await {
-                    Ok(_) => println!("SUCCESS: offered rotation capability"),
-                    Err(e) => {
-                        println!("ERROR: could not offer rotation capability, message: {}", e);
-                    }
-                }
+                offer_rotation_capability
+                    .run(sender)
+                    .await
+                    .context("could not offer rotation capability")?;
+                println!("SUCCESS: offered rotation capability");
             }
         }
 
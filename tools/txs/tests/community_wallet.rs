@@ -30,6 +30,8 @@ async fn migrate_community_wallet_with_flag() {
             migrate_n: Some(2)
         }))),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(s.encoded_pri_key.clone()), //TODO: Use mocked migrated state v6 community wallet
         chain_id: None,
         config_path: Some(dir.path().to_owned().join("libra-cli-config.yaml")),
@@ -37,6 +39,7 @@ async fn migrate_community_wallet_with_flag() {
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
     };
 
     cli_set_community_wallet.run()
@@ -71,6 +74,8 @@ async fn migrate_community_wallet() {
             migrate_n: None
         }))),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(s.encoded_pri_key.clone()), //TODO: Use mocked migrated state v6 community wallet
         chain_id: None,
         config_path: Some(dir.path().to_owned().join("libra-cli-config.yaml")),
@@ -78,6 +83,7 @@ async fn migrate_community_wallet() {
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
     };
 
     cli_set_community_wallet.run()
@@ -109,6 +115,8 @@ async fn create_community_wallet_with_flag() -> Result<(), anyhow::Error> {
             migrate_n: Some(2)
         }))),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(s.encoded_pri_key.clone()), //Issue using the private key private_key.to_string()
         chain_id: None,
         config_path: Some(dir.path().to_owned().join("libra-cli-config.yaml")),
@@ -116,6 +124,7 @@ async fn create_community_wallet_with_flag() -> Result<(), anyhow::Error> {
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
     };
 
     cli_set_community_wallet.run()
@@ -168,6 +177,8 @@ async fn new_community_wallet_cant_transfer() -> Result<(), anyhow::Error> {
                 amount: 10.0,
             }),
             mnemonic: None,
+            passphrase: false,
+            profile: None,
             test_private_key: Some(validator_private_key.clone()),
             chain_id: None,
             config_path: Some(dir.path().to_owned().join("libra-cli-config.yaml")),
@@ -175,6 +186,7 @@ async fn new_community_wallet_cant_transfer() -> Result<(), anyhow::Error> {
             tx_profile: None,
             tx_cost: Some(TxCost::default_baseline_cost()),
             estimate_only: false,
+            auto_gas: false,
         };
 
         // Execute the transfer
@@ -205,6 +217,8 @@ async fn new_community_wallet_cant_transfer() -> Result<(), anyhow::Error> {
             amount: 1.0,
         }),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(private_key_of_fifth_signer),
         chain_id: None,
         config_path: Some(dir.path().to_owned().join("libra-cli-config.yaml")),
@@ -212,6 +226,7 @@ async fn new_community_wallet_cant_transfer() -> Result<(), anyhow::Error> {
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
     };
 
     cli_transfer.run()
@@ -232,6 +247,8 @@ async fn new_community_wallet_cant_transfer() -> Result<(), anyhow::Error> {
             migrate_n: None
         }))),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(s.encoded_pri_key.clone()),
         chain_id: None,
         config_path: Some(dir.path().to_owned().join("libra-cli-config.yaml")),
@@ -239,6 +256,7 @@ async fn new_community_wallet_cant_transfer() -> Result<(), anyhow::Error> {
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
     };
 
     cli_set_community_wallet.run()
@@ -259,6 +277,8 @@ async fn new_community_wallet_cant_transfer() -> Result<(), anyhow::Error> {
             amount: 1.0,
         }),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(s.encoded_pri_key.clone()),
         chain_id: None,
         config_path: Some(dir.path().to_owned().join("libra-cli-config.yaml")),
@@ -266,6 +286,7 @@ async fn new_community_wallet_cant_transfer() -> Result<(), anyhow::Error> {
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
     };
 
     match transfer_cli.run().await {
@@ -669,6 +690,8 @@ async fn community_wallet_payment() -> Result<(), anyhow::Error> {
                 amount: 10.0, // Specified transfer amount
             }),
             mnemonic: None,
+            passphrase: false,
+            profile: None,
             test_private_key: Some(validator_private_key.clone()), // Validator's private key for authentication
             chain_id: None,
             config_path: Some(dir.path().to_owned().join("libra-cli-config.yaml")),
@@ -676,6 +699,7 @@ async fn community_wallet_payment() -> Result<(), anyhow::Error> {
             tx_profile: None,
             tx_cost: Some(TxCost::default_baseline_cost()),
             estimate_only: false,
+            auto_gas: false,
         };
 
         // Execute funds transfer to the account
@@ -704,6 +728,8 @@ async fn community_wallet_payment() -> Result<(), anyhow::Error> {
             amount: 1.0,
         }),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(private_key_of_fifth_signer),
         chain_id: None,
         config_path: Some(dir.path().to_owned().join("libra-cli-config.yaml")),
@@ -711,6 +737,7 @@ async fn community_wallet_payment() -> Result<(), anyhow::Error> {
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
     };
 
     // Execute the transfer to the new admin account
@@ -732,6 +759,8 @@ async fn community_wallet_payment() -> Result<(), anyhow::Error> {
             migrate_n: None
         }))),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(s.encoded_pri_key.clone()),
         chain_id: None,
         config_path: Some(dir.path().to_owned().join("libra-cli-config.yaml")),
@@ -739,6 +768,7 @@ async fn community_wallet_payment() -> Result<(), anyhow::Error> {
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
     };
 
     // Execute community wallet creation
@@ -756,6 +786,8 @@ async fn community_wallet_payment() -> Result<(), anyhow::Error> {
     let cli_finalize_cage = TxsCli {
         subcommand: Some(TxsSub::Community(CommunityTxs::FinalizeAndCage(FinalizeCageTx {}))),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(s.encoded_pri_key.clone()),
         chain_id: None,
         config_path: Some(dir.path().to_owned().join("libra-cli-config.yaml")),
@@ -763,6 +795,7 @@ async fn community_wallet_payment() -> Result<(), anyhow::Error> {
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
     };
     cli_finalize_cage.run()
         .await
@@ -785,13 +818,17 @@ async fn community_wallet_payment() -> Result<(), anyhow::Error> {
             description: "Thanks Mate".to_string(),
         }))),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(private_key_of_first_signer),
         chain_id: None,
         config_path: Some(dir.path().to_owned().join("libra-cli-config.yaml")),
         url: Some(s.api_endpoint.clone()),
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
-        estimate_only: false,    };
+        estimate_only: false,
+        auto_gas: false,
+    };
 
     // Execute the payment proposal
     cli_propose_payment.run()
@@ -862,6 +899,8 @@ async fn add_community_wallet_admin() -> Result<(), anyhow::Error> {
             epochs: Some(10),
         }))),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(private_key_of_first_signer),
         chain_id: None,
         config_path: Some(dir.path().to_owned().join("libra-cli-config.yaml")),
@@ -869,6 +908,7 @@ async fn add_community_wallet_admin() -> Result<(), anyhow::Error> {
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
         legacy_address: false,
     };
 
@@ -922,6 +962,8 @@ async fn add_community_wallet_admin() -> Result<(), anyhow::Error> {
                 epochs: Some(10),
             }))),
             mnemonic: None,
+            passphrase: false,
+            profile: None,
             test_private_key: Some(private_key_of_signer),
             chain_id: None,
             config_path: Some(dir.path().to_owned().join("libra-cli-config.yaml")),
@@ -929,6 +971,7 @@ async fn add_community_wallet_admin() -> Result<(), anyhow::Error> {
             tx_profile: None,
             tx_cost: Some(TxCost::default_baseline_cost()),
             estimate_only: false,
+            auto_gas: false,
             legacy_address: false,
         };
 
@@ -1100,6 +1143,8 @@ async fn remove_community_wallet_admin() -> Result<(), anyhow::Error> {
             epochs: Some(10),
         }))),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(private_key_of_first_signer),
         chain_id: None,
         config_path: Some(dir.path().to_owned().join("libra-cli-config.yaml")),
@@ -1107,6 +1152,7 @@ async fn remove_community_wallet_admin() -> Result<(), anyhow::Error> {
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
         legacy_address: false,
     };
 
@@ -1161,6 +1207,8 @@ async fn remove_community_wallet_admin() -> Result<(), anyhow::Error> {
                 epochs: Some(10),
             }))),
             mnemonic: None,
+            passphrase: false,
+            profile: None,
             test_private_key: Some(private_key_of_signer),
             chain_id: None,
             config_path: Some(dir.path().to_owned().join("libra-cli-config.yaml")),
@@ -1168,6 +1216,7 @@ async fn remove_community_wallet_admin() -> Result<(), anyhow::Error> {
             tx_profile: None,
             tx_cost: Some(TxCost::default_baseline_cost()),
             estimate_only: false,
+            auto_gas: false,
             legacy_address: false,
         };
 
@@ -1277,6 +1326,8 @@ async fn cancel_community_wallet_payment() -> Result<(), anyhow::Error> {
                 amount: 10.0, // Adjust the amount as needed
             }),
             mnemonic: None,
+            passphrase: false,
+            profile: None,
             test_private_key: Some(validator_private_key.clone()), // Use the corresponding validator's private key
             chain_id: None,
             config_path: Some(dir.path().to_owned().join("libra-cli-config.yaml")),
@@ -1284,6 +1335,7 @@ async fn cancel_community_wallet_payment() -> Result<(), anyhow::Error> {
             tx_profile: None,
             tx_cost: Some(TxCost::default_baseline_cost()),
             estimate_only: false,
+            auto_gas: false,
         };
 
         // Execute the transfer
@@ -1313,6 +1365,8 @@ async fn cancel_community_wallet_payment() -> Result<(), anyhow::Error> {
             amount: 1.0,
         }),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(private_key_of_fifth_signer),
         chain_id: None,
         config_path: Some(dir.path().to_owned().join("libra-cli-config.yaml")),
@@ -1320,6 +1374,7 @@ async fn cancel_community_wallet_payment() -> Result<(), anyhow::Error> {
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
     };
 
     cli_transfer.run()
@@ -1340,6 +1395,8 @@ async fn cancel_community_wallet_payment() -> Result<(), anyhow::Error> {
             migrate_n: None
         }))),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(s.encoded_pri_key.clone()),
         chain_id: None,
         config_path: Some(dir.path().to_owned().join("libra-cli-config.yaml")),
@@ -1347,6 +1404,7 @@ async fn cancel_community_wallet_payment() -> Result<(), anyhow::Error> {
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
     };
 
     cli_set_community_wallet.run()
@@ -1364,6 +1422,8 @@ async fn cancel_community_wallet_payment() -> Result<(), anyhow::Error> {
     let cli_finalize_cage = TxsCli {
         subcommand: Some(TxsSub::Community(CommunityTxs::FinalizeAndCage(FinalizeCageTx {}))),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(s.encoded_pri_key.clone()),
         chain_id: None,
         config_path: Some(dir.path().to_owned().join("libra-cli-config.yaml")),
@@ -1371,6 +1431,7 @@ async fn cancel_community_wallet_payment() -> Result<(), anyhow::Error> {
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
     };
 
     cli_finalize_cage.run()
@@ -1391,6 +1452,8 @@ async fn cancel_community_wallet_payment() -> Result<(), anyhow::Error> {
             description: "Thanks Mate".to_string(),
         }))),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(private_key_of_first_signer),
         chain_id: None,
         config_path: Some(dir.path().to_owned().join("libra-cli-config.yaml")),
@@ -1398,6 +1461,7 @@ async fn cancel_community_wallet_payment() -> Result<(), anyhow::Error> {
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
     };
 
     cli_propose_payment.run()
@@ -1421,6 +1485,8 @@ async fn cancel_community_wallet_payment() -> Result<(), anyhow::Error> {
             description: "Thanks Mate".to_string(),
         }))),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(private_key_of_second_signer),
         chain_id: None,
         config_path: Some(dir.path().to_owned().join("libra-cli-config.yaml")),
@@ -1428,6 +1494,7 @@ async fn cancel_community_wallet_payment() -> Result<(), anyhow::Error> {
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
     };
 
     cli_propose_payment_signer_two.run()
@@ -1462,6 +1529,8 @@ async fn cancel_community_wallet_payment() -> Result<(), anyhow::Error> {
             amount: 1.0,
         }),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(private_key_of_forth_signer.clone()), // Use the corresponding validator's private key
         chain_id: None,
         config_path: Some(dir.path().to_owned().join("libra-cli-config.yaml")),
@@ -1469,6 +1538,7 @@ async fn cancel_community_wallet_payment() -> Result<(), anyhow::Error> {
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
     };
 
     // Execute the transfer
@@ -1486,6 +1556,8 @@ async fn cancel_community_wallet_payment() -> Result<(), anyhow::Error> {
             proposal_id: 0,
         }))),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(private_key_of_forth_signer.clone()),
         chain_id: None,
         config_path: Some(dir.path().to_owned().join("libra-cli-config.yaml")),
@@ -1493,6 +1565,7 @@ async fn cancel_community_wallet_payment() -> Result<(), anyhow::Error> {
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
     };
 
     // Execute the VetoTx command
@@ -1542,6 +1615,8 @@ async fn setup_environment() -> (LibraSmoke, TempPath, AccountAddress, String, A
             amount: 100.0,
         }),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(s.encoded_pri_key.clone()),
         chain_id: None,
         config_path: Some(dir.path().to_owned().join("libra-cli-config.yaml")),
@@ -1549,6 +1624,7 @@ async fn setup_environment() -> (LibraSmoke, TempPath, AccountAddress, String, A
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
         legacy_address: false,
     };
 
@@ -1593,6 +1669,8 @@ async fn run_cli_transfer(
     let cli_transfer = TxsCli {
         subcommand: Some(Transfer { to_account, amount }),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(private_key),
         chain_id: None,
         config_path: Some(config_path),
@@ -1600,6 +1678,7 @@ async fn run_cli_transfer(
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
         legacy_address: false,
     };
 
@@ -1624,6 +1703,8 @@ async fn run_cli_community_init(
             num_signers,
         }))),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(donor_private_key),
         chain_id: None,
         config_path: Some(config_path),
@@ -1631,6 +1712,7 @@ async fn run_cli_community_init(
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
         legacy_address: false,
     };
 
@@ -1652,6 +1734,8 @@ async fn run_cli_claim_offer(
             community_wallet: community_address,
         }))),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(signer_pk),
         chain_id: None,
         config_path: Some(config_path),
@@ -1659,6 +1743,7 @@ async fn run_cli_claim_offer(
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
         legacy_address: false,
     };
 
@@ -1679,6 +1764,8 @@ async fn run_cli_community_cage(
             num_signers,
         }))),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(donor_private_key),
         chain_id: None,
         config_path: Some(config_path),
@@ -1686,6 +1773,7 @@ async fn run_cli_community_cage(
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
         legacy_address: false,
     };
 
@@ -1708,6 +1796,8 @@ async fn run_cli_community_propose_offer(
             num_signers,
         }))),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(donor_private_key),
         chain_id: None,
         config_path: Some(config_path),
@@ -1715,6 +1805,7 @@ async fn run_cli_community_propose_offer(
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
         legacy_address: false,
     };
 
@@ -1798,6 +1889,8 @@ async fn test_offer_migration() -> Result<(), anyhow::Error> {
     let init_gov_deprecated = TxsCli {
         subcommand: Some(TxsSub::Community(CommunityTxs::GovInitDeprectated)),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(community_wallet_pk.clone()),
         chain_id: None,
         config_path: Some(config_path.clone()),
@@ -1805,6 +1898,7 @@ async fn test_offer_migration() -> Result<(), anyhow::Error> {
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
         legacy_address: false,
     };
 
@@ -1834,6 +1928,8 @@ async fn test_offer_migration() -> Result<(), anyhow::Error> {
             community_wallet: community_wallet_address,
         }))),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(community_wallet_pk),
         chain_id: None,
         config_path: Some(config_path),
@@ -1841,6 +1937,7 @@ async fn test_offer_migration() -> Result<(), anyhow::Error> {
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
         legacy_address: false,
     };
 
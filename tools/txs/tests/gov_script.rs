@@ -35,8 +35,11 @@ async fn smoke_gov_script() {
         subcommand: Some(Governance(Propose {
             proposal_script_dir: script_dir.clone(),
             metadata_url: "http://allyourbase.com".to_string(),
+            metadata_file: None,
         })),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(s.encoded_pri_key.clone()),
         chain_id: None,
         config_path: Some(d.path().to_owned().join("libra-cli-config.yaml")),
@@ -44,6 +47,7 @@ async fn smoke_gov_script() {
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
         legacy_address: false,
     };
 
@@ -55,6 +59,7 @@ async fn smoke_gov_script() {
     cli.subcommand = Some(Governance(Vote {
         proposal_id: 0,
         should_fail: false,
+        skip_metadata_check: false,
     }));
     cli.run().await.unwrap();
 
@@ -101,3 +106,102 @@ async fn smoke_gov_script() {
     }));
     cli.run().await.unwrap();
 }
+
+/// The proposer puts the hash of the metadata document on chain, and a voter
+/// who downloads a different document is stopped, unless they skip the check.
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn smoke_gov_metadata_check() -> anyhow::Result<()> {
+    use libra_query::gov_queries::get_proposal;
+    use libra_types::core_types::proposal_metadata::{ProposalMetadata, ScriptStep};
+
+    let d = diem_temppath::TempPath::new();
+
+    let mut s = LibraSmoke::new(Some(2), None)
+        .await
+        .expect("could not start libra smoke");
+
+    configure_validator::init_val_config_files(&mut s.swarm, 0, Some(d.path().to_owned()))
+        .expect("could not init validator config");
+
+    let this_path = PathBuf::from_str(env!("CARGO_MANIFEST_DIR")).unwrap();
+    let script_dir = this_path.join("tests/fixtures/governance_script_template");
+
+    let meta = ProposalMetadata {
+        title: "test upgrade".to_owned(),
+        steps: vec![ScriptStep {
+            name: "governance_script_template".to_owned(),
+            script_hash: std::fs::read_to_string(script_dir.join("script_sha3"))?,
+        }],
+        ..Default::default()
+    };
+    let meta_path = meta.save(d.path())?;
+    let (_, hash) = ProposalMetadata::read(&meta_path)?;
+
+    // what gets published is not the document which was proposed
+    let mut tampered = meta.clone();
+    tampered.description = "something else".to_owned();
+    let url = serve_file(serde_json::to_vec_pretty(&tampered)?).await?;
+
+    let mut cli = TxsCli {
+        subcommand: Some(Governance(Propose {
+            proposal_script_dir: script_dir,
+            metadata_url: url,
+            metadata_file: Some(meta_path),
+        })),
+        mnemonic: None,
+        passphrase: false,
+        profile: None,
+        test_private_key: Some(s.encoded_pri_key.clone()),
+        chain_id: None,
+        config_path: Some(d.path().to_owned().join("libra-cli-config.yaml")),
+        url: Some(s.api_endpoint.clone()),
+        tx_profile: None,
+        tx_cost: Some(TxCost::default_baseline_cost()),
+        estimate_only: false,
+        auto_gas: false,
+        legacy_address: false,
+    };
+    cli.run().await?;
+
+    let proposal = get_proposal(&s.client(), 0).await?;
+    assert_eq!(proposal.metadata_hash, Some(hash.to_hex()));
+
+    cli.subcommand = Some(Governance(Vote {
+        proposal_id: 0,
+        should_fail: false,
+        skip_metadata_check: false,
+    }));
+    assert!(cli.run().await.is_err(), "voted on a mismatched document");
+    assert_eq!(get_proposal(&s.client(), 0).await?.yes_votes, 0);
+
+    cli.subcommand = Some(Governance(Vote {
+        proposal_id: 0,
+        should_fail: false,
+        skip_metadata_check: true,
+    }));
+    cli.run().await?;
+    assert!(get_proposal(&s.client(), 0).await?.yes_votes > 0);
+
+    Ok(())
+}
+
+/// serve a file over http on a local port, returns its url
+async fn serve_file(body: Vec<u8>) -> anyhow::Result<String> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}/proposal_metadata.json", listener.local_addr()?);
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request).await;
+            let head = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                body.len()
+            );
+            let _ = stream.write_all(head.as_bytes()).await;
+            let _ = stream.write_all(&body).await;
+        }
+    });
+    Ok(url)
+}
@@ -0,0 +1,149 @@
+use diem_sdk::types::transaction::TransactionPayload;
+use libra_smoke_tests::{helpers::get_libra_balance, libra_smoke::LibraSmoke};
+use libra_txs::{
+    generic_tx::build_entry_function,
+    offline::{
+        build_unsigned, read_signed, save_signed, sign_unsigned, submit_signed, UnsignedTx,
+        DEFAULT_OFFLINE_EXPIRY_SECS,
+    },
+};
+use libra_types::core_types::app_cfg::TxCost;
+
+// Scenario: the online machine builds an unsigned transfer, the offline
+// machine signs it with only the private key, and it gets submitted
+// from the files.
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn offline_sign_and_submit() -> anyhow::Result<()> {
+    let d = diem_temppath::TempPath::new();
+    d.create_as_dir()?;
+
+    let mut s = LibraSmoke::new(None, None)
+        .await
+        .expect("could not start libra smoke");
+    let client = s.client();
+    let recipient = s.marlon_rando().address();
+
+    // 1. online, no keys
+    let entry = build_entry_function(
+        "0x1::ol_account::transfer",
+        &None,
+        &Some(format!("0x{}, 1000000", recipient.to_hex())),
+    )?;
+    let unsigned = build_unsigned(
+        &client,
+        s.first_account.address(),
+        vec![],
+        TransactionPayload::EntryFunction(entry),
+        &TxCost::default_baseline_cost(),
+        DEFAULT_OFFLINE_EXPIRY_SECS,
+    )
+    .await?;
+    let unsigned_path = d.path().join("tx.unsigned.json");
+    unsigned.save(&unsigned_path)?;
+
+    // 2. offline, only the key
+    let unsigned = UnsignedTx::read(&unsigned_path)?;
+    let signed = sign_unsigned(&unsigned, s.first_account.private_key())?;
+    let signed_path = d.path().join("tx.signed");
+    save_signed(&signed, &signed_path)?;
+
+    // the signer sees what is called
+    assert_eq!(unsigned.payload.function_id, "0x1::ol_account::transfer");
+    assert_eq!(unsigned.payload.args.len(), 2);
+
+    // tampering with the readable fields is detected
+    let mut tampered = unsigned.clone();
+    tampered.sequence_number += 1;
+    assert!(sign_unsigned(&tampered, s.first_account.private_key()).is_err());
+    let mut tampered = unsigned.clone();
+    tampered.payload.args[0] = format!("0x{}", s.first_account.address().to_hex());
+    assert!(sign_unsigned(&tampered, s.first_account.private_key()).is_err());
+
+    // 3. online again
+    let res = submit_signed(&client, &read_signed(&signed_path)?).await?;
+    assert!(res.info.status().is_success());
+
+    let bal = get_libra_balance(&client, recipient).await?;
+    assert_eq!(bal.total, 1_000_000);
+
+    Ok(())
+}
+
+/// co-signers of a multi-agent transaction exchange partial signatures,
+/// no network needed
+#[test]
+fn multi_agent_partial_signatures() -> anyhow::Result<()> {
+    use diem_sdk::{transaction_builder::TransactionBuilder, types::chain_id::ChainId};
+    use libra_txs::offline::{combine_partials, sign_partial};
+    use libra_wallet::account_keys::get_keys_from_mnem;
+
+    let keys = get_keys_from_mnem("talent sunset lizard pill fame nuclear spy noodle basket okay critic grow sleep legend hurry pitch blanket clerk impose rough degree sock insane purse".to_owned())?;
+    let alice = keys.child_0_owner;
+    let bob = keys.child_1_operator;
+
+    let entry = build_entry_function(
+        "0x1::ol_account::transfer",
+        &None,
+        &Some("0x1, 1".to_owned()),
+    )?;
+    let raw = TransactionBuilder::new(
+        TransactionPayload::EntryFunction(entry),
+        u64::MAX,
+        ChainId::test(),
+    )
+    .sender(alice.account)
+    .sequence_number(0)
+    .build();
+    let mut unsigned = UnsignedTx::new(&raw)?;
+    unsigned.secondary_signers = vec![bob.account];
+
+    // the whole transaction cannot be signed by one signer
+    assert!(sign_unsigned(&unsigned, &alice.pri_key).is_err());
+    // only signers can sign
+    assert!(sign_partial(&unsigned, &alice.pri_key, keys.child_2_val_network.account).is_err());
+
+    let alice_sig = sign_partial(&unsigned, &alice.pri_key, alice.account)?;
+    let bob_sig = sign_partial(&unsigned, &bob.pri_key, bob.account)?;
+
+    // missing a signer
+    assert!(combine_partials(&unsigned, &[alice_sig.clone()]).is_err());
+    // a signature of another transaction
+    let mut other = unsigned.clone();
+    other.secondary_signers = vec![keys.child_2_val_network.account];
+    let mut stale = sign_partial(&other, &bob.pri_key, keys.child_2_val_network.account)?;
+    stale.signer = bob.account;
+    assert!(combine_partials(&unsigned, &[alice_sig.clone(), stale]).is_err());
+
+    let signed = combine_partials(&unsigned, &[bob_sig, alice_sig])?;
+    assert!(signed.check_signature().is_ok());
+
+    Ok(())
+}
+
+/// sponsored transactions are refused before anything is built
+#[tokio::test]
+async fn fee_payer_is_not_supported() {
+    use clap::Parser;
+    use libra_txs::txs_cli::TxsCli;
+    use libra_types::core_types::cli_error::{CliError, ErrorCategory};
+
+    let cli = TxsCli::try_parse_from([
+        "txs",
+        "build-unsigned",
+        "--sender",
+        "0x1",
+        "--function-id",
+        "0x1::ol_account::transfer",
+        "--output",
+        "unsigned.json",
+        "--fee-payer",
+        "0x2",
+    ])
+    .unwrap();
+    let err = cli.run().await.unwrap_err();
+    assert_eq!(
+        CliError::from_anyhow(&err).category,
+        ErrorCategory::BadInput
+    );
+}
@@ -44,6 +44,8 @@ async fn smoke_onboard_validator() -> anyhow::Result<()> {
             amount: 1.0,
         }),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(s.encoded_pri_key.clone()),
         chain_id: None,
         config_path: Some(d.path().to_owned().join("libra-cli-config.yaml")),
@@ -51,6 +53,7 @@ async fn smoke_onboard_validator() -> anyhow::Result<()> {
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
         legacy_address: false,
     };
 
@@ -70,6 +73,8 @@ async fn smoke_onboard_validator() -> anyhow::Result<()> {
             operator_file: Some(operator_file),
         })),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(
             "0x74f18da2b80b1820b58116197b1c41f8a36e1b37a15c7fb434bb42dd7bdaa66b".to_owned(),
         ),
@@ -79,6 +84,7 @@ async fn smoke_onboard_validator() -> anyhow::Result<()> {
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
         legacy_address: false,
     };
 
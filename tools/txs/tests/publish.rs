@@ -34,6 +34,8 @@ async fn smoke_publish() {
     let mut cli = TxsCli {
         subcommand: Some(Publish(move_package)),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(s.encoded_pri_key.clone()),
         chain_id: None,
         config_path: Some(d.path().to_owned().join("libra-cli-config.yaml")),
@@ -41,6 +43,7 @@ async fn smoke_publish() {
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
         legacy_address: false,
     };
 
@@ -1,5 +1,6 @@
+use diem_sdk::types::transaction::{authenticator::TransactionAuthenticator, TransactionPayload};
 use libra_smoke_tests::libra_smoke::LibraSmoke;
-use libra_txs::submit_transaction::Sender;
+use libra_txs::{generic_tx::build_entry_function, submit_transaction::Sender};
 use libra_types::core_types::app_cfg::Profile;
 use libra_wallet::account_keys;
 
@@ -53,3 +54,33 @@ async fn sender_back_and_forth() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// The sender and the secondary signers all sign a multi-agent transaction
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn sender_signs_multi_agent() -> anyhow::Result<()> {
+    let mut ls = LibraSmoke::new(Some(1), None)
+        .await
+        .expect("could not start libra smoke");
+    let val_app_cfg = ls.first_account_app_cfg()?;
+    let mut s = Sender::from_app_cfg(&val_app_cfg, None).await?;
+    let bob = ls.marlon_rando();
+
+    let payload = TransactionPayload::EntryFunction(build_entry_function(
+        "0x1::ol_account::transfer",
+        &None,
+        &Some(format!("{}, 1", bob.address())),
+    )?);
+    let signed = s.sign_multi_agent_payload(payload, vec![&bob]);
+
+    assert!(signed.check_signature().is_ok());
+    assert_eq!(signed.sender(), ls.first_account.address());
+    match signed.authenticator() {
+        TransactionAuthenticator::MultiAgent {
+            secondary_signer_addresses,
+            ..
+        } => assert_eq!(secondary_signer_addresses, vec![bob.address()]),
+        _ => panic!("not a multi-agent transaction"),
+    }
+
+    Ok(())
+}
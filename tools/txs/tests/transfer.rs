@@ -35,6 +35,8 @@ async fn smoke_transfer_existing_account() {
             amount: 1.0,
         }),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(s.encoded_pri_key.clone()),
         chain_id: None,
         config_path: Some(d.path().to_owned().join("libra-cli-config.yaml")),
@@ -42,6 +44,7 @@ async fn smoke_transfer_existing_account() {
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
         legacy_address: false,
     };
 
@@ -75,6 +78,8 @@ async fn smoke_transfer_create_account() -> Result<(), anyhow::Error> {
             amount: 1.0,
         }),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(s.encoded_pri_key.clone()),
         chain_id: None,
         config_path: Some(d.path().to_owned().join("libra-cli-config.yaml")),
@@ -82,6 +87,7 @@ async fn smoke_transfer_create_account() -> Result<(), anyhow::Error> {
         tx_profile: None,
         tx_cost: Some(TxCost::default_baseline_cost()),
         estimate_only: false,
+        auto_gas: false,
         legacy_address: false,
     };
 
@@ -118,6 +124,8 @@ async fn smoke_transfer_estimate() {
             amount: 1.0,
         }),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(s.encoded_pri_key.clone()),
         chain_id: None,
         config_path: Some(d.path().to_owned().join("libra-cli-config.yaml")),
@@ -125,6 +133,7 @@ async fn smoke_transfer_estimate() {
         tx_profile: None,
         tx_cost: Some(TxCost::default_cheap_txs_cost()),
         estimate_only: true, // THIS IS THE TEST
+        auto_gas: false,
         legacy_address: false,
     };
 
@@ -0,0 +1,75 @@
+use libra_smoke_tests::{configure_validator, helpers::get_libra_balance, libra_smoke::LibraSmoke};
+use libra_txs::{
+    transfer_batch::{PaymentResult, PaymentStatus, TransferBatchTx},
+    txs_cli::{TxsCli, TxsSub::TransferBatch},
+};
+use libra_types::core_types::app_cfg::TxCost;
+
+// Scenario: pay two new accounts from a CSV file, then run the same
+// batch again, as after a crash. Nobody gets paid twice.
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn transfer_batch_resumes_without_double_pay() -> anyhow::Result<()> {
+    let d = diem_temppath::TempPath::new();
+
+    let mut s = LibraSmoke::new(None, None)
+        .await
+        .expect("could not start libra smoke");
+
+    let (_, _app_cfg) =
+        configure_validator::init_val_config_files(&mut s.swarm, 0, Some(d.path().to_owned()))
+            .expect("could not init validator config");
+
+    let client = s.client();
+    let alice = s.marlon_rando().address();
+    let bob = s.marlon_rando().address();
+
+    let file = d.path().join("payments.csv");
+    std::fs::write(
+        &file,
+        format!(
+            "recipient,amount,note\n{},1.0,first\n{},2.5,\n",
+            alice.to_hex_literal(),
+            bob.to_hex_literal()
+        ),
+    )?;
+
+    let cli = || TxsCli {
+        subcommand: Some(TransferBatch(TransferBatchTx {
+            file: file.clone(),
+            out: None,
+            pipeline: 1,
+            check: false,
+        })),
+        mnemonic: None,
+        passphrase: false,
+        profile: None,
+        test_private_key: Some(s.encoded_pri_key.clone()),
+        chain_id: None,
+        config_path: Some(d.path().to_owned().join("libra-cli-config.yaml")),
+        url: Some(s.api_endpoint.clone()),
+        tx_profile: None,
+        tx_cost: Some(TxCost::default_baseline_cost()),
+        estimate_only: false,
+        auto_gas: false,
+        legacy_address: false,
+    };
+
+    cli().run().await.expect("could not send the batch");
+
+    let results: Vec<PaymentResult> = serde_json::from_str(&std::fs::read_to_string(
+        d.path().join("payments.results.json"),
+    )?)?;
+    assert_eq!(results.len(), 2);
+    assert!(results
+        .iter()
+        .all(|r| r.status == PaymentStatus::Success && r.hash.is_some()));
+
+    // run it again, everything was already paid
+    cli().run().await.expect("could not resume the batch");
+
+    assert_eq!(get_libra_balance(&client, alice).await?.total, 1_000_000);
+    assert_eq!(get_libra_balance(&client, bob).await?.total, 2_500_000);
+
+    Ok(())
+}
@@ -1,10 +1,14 @@
 //! Use ol-keys to generate or parse keys using the legacy key derivation scheme
 use crate::{
-    core::{legacy_scheme::LegacyKeyScheme, wallet_library::WalletLibrary},
+    core::{
+        key_factory::{ChildNumber, ExtendedPrivKey},
+        legacy_scheme::LegacyKeyScheme,
+        wallet_library::WalletLibrary,
+    },
     key_gen::keygen,
     load_keys,
 };
-use anyhow::Result;
+use anyhow::{bail, Result};
 use diem_crypto::{ed25519::Ed25519PrivateKey, PrivateKey};
 use diem_types::{account_address::AccountAddress, transaction::authenticator::AuthenticationKey};
 use serde::Serialize;
@@ -57,8 +61,8 @@ pub fn get_ol_legacy_address(addr: AccountAddress) -> anyhow::Result<AccountAddr
 
 /// Derive keys from a mnemonic in the 0L scheme
 // NOTE: these keys are not sufficient to create a validator from V7 onwards. There are BLS keys needed in additiont o Ed25519
-pub fn legacy_keygen(danger_print: bool) -> Result<KeyChain> {
-    let (auth_key, account, wallet, mnem) = keygen();
+pub fn legacy_keygen(danger_print: bool, passphrase: &str) -> Result<KeyChain> {
+    let (auth_key, account, wallet, mnem) = keygen(passphrase);
 
     //////////////// Info ////////////////
 
@@ -85,6 +89,12 @@ pub fn legacy_keygen(danger_print: bool) -> Result<KeyChain> {
         //use same styles as abscissa_info
         println!("\x1b[1;36m{}\n\x1b[0m", mnem.as_str());
 
+        if !passphrase.is_empty() {
+            println!(
+                "The mnemonic alone does not recover this account, you also need the passphrase.\n"
+            );
+        }
+
         println!(
             "WRITE THIS DOWN NOW. This is the last time you will see \
                     this mnemonic. It is not saved anywhere. Nobody can help \
@@ -103,7 +113,13 @@ pub fn get_keys_from_prompt() -> Result<KeyChain> {
 
 /// for libs to get the keys from a mnemonic
 pub fn get_keys_from_mnem(mnem: String) -> Result<KeyChain> {
-    let (_auth_key, _account, wallet) = load_keys::get_account_from_mnem(mnem)?;
+    get_keys_from_mnem_with_passphrase(mnem, "")
+}
+
+/// get the keys from a mnemonic and the optional passphrase
+pub fn get_keys_from_mnem_with_passphrase(mnem: String, passphrase: &str) -> Result<KeyChain> {
+    let (_auth_key, _account, wallet) =
+        load_keys::get_account_from_mnem_with_passphrase(mnem, passphrase)?;
     KeyChain::new(&wallet)
 }
 
@@ -131,7 +147,10 @@ fn get_account_from_nth(w: &WalletLibrary, n: u8) -> Result<AccountKeys> {
         5 => pri_keys.child_5_executor,
         _ => panic!("Invalid key index"),
     };
+    account_keys_from_child(&key)
+}
 
+fn account_keys_from_child(key: &ExtendedPrivKey) -> Result<AccountKeys> {
     let auth_key = key.get_authentication_key();
     let account = key.get_address();
     Ok(AccountKeys {
@@ -141,6 +160,43 @@ fn get_account_from_nth(w: &WalletLibrary, n: u8) -> Result<AccountKeys> {
     })
 }
 
+//////// 0L ////////
+/// Child keys 0 to 5 are the owner and the validator roles of the KeyChain.
+/// Other accounts of the same mnemonic, e.g. sub-accounts and hot wallets, start here.
+pub const FIRST_USER_ACCOUNT_INDEX: u64 = 6;
+
+/// The account of the child key at an index. Index 0 is the owner account.
+pub fn get_account_at_index(w: &WalletLibrary, index: u64) -> Result<AccountKeys> {
+    if index != 0 && index < FIRST_USER_ACCOUNT_INDEX {
+        bail!(
+            "child keys 1 to {} are reserved for the validator keys, use {} or above",
+            FIRST_USER_ACCOUNT_INDEX - 1,
+            FIRST_USER_ACCOUNT_INDEX
+        );
+    }
+    let key = w.get_key_factory().private_child(ChildNumber::new(index))?;
+    account_keys_from_child(&key)
+}
+
+/// The private key of the account at an index of a mnemonic and the optional
+/// passphrase, e.g. for a profile
+pub fn get_key_at_index_from_mnem(
+    mnem: String,
+    passphrase: &str,
+    index: u64,
+) -> Result<Ed25519PrivateKey> {
+    let (_auth_key, _account, wallet) =
+        load_keys::get_account_from_mnem_with_passphrase(mnem, passphrase)?;
+    Ok(get_account_at_index(&wallet, index)?.pri_key)
+}
+
+/// Prompts for the mnemonic, and gets the private key of the account at an index
+pub fn get_key_at_index_from_prompt(passphrase: &str, index: u64) -> Result<Ed25519PrivateKey> {
+    let (_auth_key, _account, wallet) =
+        load_keys::get_account_from_prompt_with_passphrase(passphrase);
+    Ok(get_account_at_index(&wallet, index)?.pri_key)
+}
+
 impl KeyChain {
     pub fn new(w: &WalletLibrary) -> Result<Self> {
         Ok(KeyChain {
@@ -221,3 +277,69 @@ fn type_conversion_give_same_auth_and_address() {
         l.child_0_owner.auth_key.to_string()
     );
 }
+
+#[test]
+fn passphrase_keys_differ() {
+    let alice_mnem = "talent sunset lizard pill fame nuclear spy noodle basket okay critic grow sleep legend hurry pitch blanket clerk impose rough degree sock insane purse";
+
+    // an empty passphrase is the same as none
+    let l = get_keys_from_mnem_with_passphrase(alice_mnem.to_string(), "").unwrap();
+    assert_eq!(
+        "2570472a9a08b9cc1f7c616e9ebb1dc534db452d3a3d3c567e58bec9f0fbd13e",
+        &hex::encode(&l.seed)
+    );
+
+    let p = get_keys_from_mnem_with_passphrase(alice_mnem.to_string(), "hunter2").unwrap();
+    assert_ne!(l.seed, p.seed);
+    assert_ne!(l.child_0_owner.account, p.child_0_owner.account);
+}
+
+/// an account derived with a mnemonic passphrase signs for its own address
+#[test]
+fn sign_with_passphrase_key() {
+    use diem_crypto::{Signature, SigningKey};
+
+    let alice_mnem = "talent sunset lizard pill fame nuclear spy noodle basket okay critic grow sleep legend hurry pitch blanket clerk impose rough degree sock insane purse";
+    let keys = get_keys_from_mnem_with_passphrase(alice_mnem.to_owned(), "hunter2").unwrap();
+    let pri_key = get_key_at_index_from_mnem(alice_mnem.to_owned(), "hunter2", 0).unwrap();
+
+    // the key of the passphrase account, not of the mnemonic alone
+    let auth_key = AuthenticationKey::ed25519(&pri_key.public_key());
+    assert_eq!(auth_key, keys.child_0_owner.auth_key);
+    assert_eq!(auth_key.derived_address(), keys.child_0_owner.account);
+    let without = get_key_at_index_from_mnem(alice_mnem.to_owned(), "", 0).unwrap();
+    assert_ne!(
+        AuthenticationKey::ed25519(&without.public_key()).derived_address(),
+        keys.child_0_owner.account
+    );
+
+    let signature = pri_key.sign_arbitrary_message(b"sent by alice");
+    assert!(signature
+        .verify_arbitrary_msg(b"sent by alice", &keys.child_0_owner.pri_key.public_key())
+        .is_ok());
+}
+
+#[test]
+fn user_accounts_at_index() {
+    use crate::load_keys::get_account_from_mnem;
+
+    let alice_mnem = "talent sunset lizard pill fame nuclear spy noodle basket okay critic grow sleep legend hurry pitch blanket clerk impose rough degree sock insane purse";
+    let (_, account, wallet) = get_account_from_mnem(alice_mnem.to_owned()).unwrap();
+
+    // index 0 is the owner
+    assert_eq!(get_account_at_index(&wallet, 0).unwrap().account, account);
+    // the validator keys are not user accounts
+    assert!(get_account_at_index(&wallet, 3).is_err());
+
+    let six = get_account_at_index(&wallet, 6).unwrap();
+    let seven = get_account_at_index(&wallet, 7).unwrap();
+    assert_ne!(six.account, account);
+    assert_ne!(six.account, seven.account);
+    assert_eq!(six.auth_key.derived_address(), six.account);
+    assert_eq!(
+        get_key_at_index_from_mnem(alice_mnem.to_owned(), "", 6)
+            .unwrap()
+            .to_bytes(),
+        six.pri_key.to_bytes()
+    );
+}
@@ -26,12 +26,14 @@ use std::ops::AddAssign;
 
 use diem_crypto::{
     compat::Sha3_256,
-    ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
     hkdf::Hkdf,
+    SigningKey,
 };
 use diem_types::{account_address::AccountAddress, transaction::authenticator::AuthenticationKey};
 
 /// Main is a set of raw bytes that are used for child key derivation
+#[derive(Clone)]
 pub struct Main([u8; 32]);
 
 // NOTE: 0L, removed macros for this implementation; however, the `From` trait is still used.
@@ -130,6 +132,13 @@ impl ExtendedPrivKey {
         AuthenticationKey::ed25519(&self.get_public())
     }
 
+    //////// 0L ////////
+    /// Sign bytes which are not a transaction, e.g. an off-chain message.
+    /// The caller is responsible for the domain separation of the bytes.
+    pub fn sign_arbitrary_message(&self, message: &[u8]) -> Ed25519Signature {
+        self.private_key.sign_arbitrary_message(message)
+    }
+
     // /// Diem specific sign function that is capable of signing an arbitrary
     // /// Serializable value.
     // ///
@@ -147,6 +156,7 @@ impl ExtendedPrivKey {
 }
 
 /// Wrapper struct from which we derive child keys
+#[derive(Clone)]
 pub struct KeyFactory {
     main: Main,
 }
@@ -41,7 +41,17 @@ impl LegacyKeyScheme {
     }
     /// Get KeyScheme from a mnemonic string.
     pub fn new_from_mnemonic(mnemonic: String) -> LegacyKeyScheme {
-        let wallet = WalletLibrary::new_from_mnemonic(Mnemonic::from(&mnemonic).unwrap());
+        Self::new_from_mnemonic_with_passphrase(mnemonic, "")
+    }
+    /// Get KeyScheme from a mnemonic string and a passphrase.
+    pub fn new_from_mnemonic_with_passphrase(
+        mnemonic: String,
+        passphrase: &str,
+    ) -> LegacyKeyScheme {
+        let wallet = WalletLibrary::new_from_mnemonic_with_passphrase(
+            Mnemonic::from(&mnemonic).unwrap(),
+            passphrase,
+        );
         LegacyKeyScheme::new(&wallet)
     }
 }
@@ -72,3 +82,44 @@ fn test_legacy_key_scheme() {
     assert!(key_scheme.child_4_consensus.get_private_key().length() > 0);
     assert!(key_scheme.child_5_executor.get_private_key().length() > 0);
 }
+
+// The passphrase is opt in: without it the keys are byte for byte the ones
+// existing accounts were created with.
+#[test]
+fn test_passphrase_is_backwards_compatible() {
+    use diem_crypto::ValidCryptoMaterial;
+
+    let alice_mnem = "talent sunset lizard pill fame nuclear spy noodle basket okay critic grow sleep legend hurry pitch blanket clerk impose rough degree sock insane purse";
+
+    let legacy = LegacyKeyScheme::new_from_mnemonic(alice_mnem.to_string());
+    let empty = LegacyKeyScheme::new_from_mnemonic_with_passphrase(alice_mnem.to_string(), "");
+    assert_eq!(
+        legacy.child_0_owner.get_private_key().to_bytes(),
+        empty.child_0_owner.get_private_key().to_bytes()
+    );
+    // the address alice always had
+    assert_eq!(
+        legacy.child_0_owner.get_address().to_hex_literal(),
+        "0x87515d94a244235a1433d7117bc0cb154c613c2f4b1e67ca8d98a542ee3f59f5"
+    );
+
+    let with_passphrase =
+        LegacyKeyScheme::new_from_mnemonic_with_passphrase(alice_mnem.to_string(), "hunter2");
+    let other_passphrase =
+        LegacyKeyScheme::new_from_mnemonic_with_passphrase(alice_mnem.to_string(), "hunter3");
+    assert_ne!(
+        legacy.child_0_owner.get_address(),
+        with_passphrase.child_0_owner.get_address()
+    );
+    assert_ne!(
+        with_passphrase.child_0_owner.get_address(),
+        other_passphrase.child_0_owner.get_address()
+    );
+    assert_ne!(
+        legacy.child_4_consensus.get_private_key().to_bytes(),
+        with_passphrase
+            .child_4_consensus
+            .get_private_key()
+            .to_bytes()
+    );
+}
@@ -38,6 +38,7 @@ use std::{
 /// |   224   |   21  |
 /// |   256   |   24  |
 /// +---------+-------+
+#[derive(Clone)]
 pub struct Mnemonic(Vec<&'static str>);
 
 impl Display for Mnemonic {
@@ -105,6 +106,20 @@ impl Mnemonic {
         Ok(Mnemonic(mnemonic))
     }
 
+    //////// 0L ////////
+    /// The entropy behind the mnemonic, without the checksum. The inverse of `new`.
+    pub fn entropy(&self) -> Vec<u8> {
+        let mut bit_writer = U11BitWriter::new(self.0.len());
+        for word in &self.0 {
+            let idx = word_index(word).expect("the words were checked when the mnemonic was made");
+            bit_writer.write_u11(idx as u16);
+        }
+        bit_writer.write_buffer();
+        // the checksum is in the last byte
+        bit_writer.bytes.pop();
+        bit_writer.bytes
+    }
+
     /// Write mnemonic to output_file_path.
     pub fn write(&self, output_file_path: &Path) -> Result<()> {
         if output_file_path.exists() && !output_file_path.is_file() {
@@ -128,6 +143,17 @@ impl Mnemonic {
     }
 }
 
+//////// 0L ////////
+/// The word of the BIP39 word list at an index, for other encodings.
+pub(crate) fn word_at(index: usize) -> Option<&'static str> {
+    WORDS.get(index).copied()
+}
+
+/// The index of a word in the BIP39 word list.
+pub(crate) fn word_index(word: &str) -> Option<usize> {
+    WORDS.binary_search(&word).ok()
+}
+
 /// BitReader reads data from a byte slice at the granularity of 11 bits.
 struct U11BitReader<'a> {
     bytes: &'a [u8],
@@ -449,6 +475,18 @@ fn test_roundtrip_mnemonic() {
     assert_eq!(mnemonic.to_string(), other_mnemonic.to_string());
 }
 
+#[test]
+fn test_entropy_roundtrip() {
+    for size in (16..=32).step_by(4) {
+        let mut entropy = vec![0u8; size];
+        OsRng.fill_bytes(&mut entropy);
+        let mnemonic = Mnemonic::new(&entropy).unwrap();
+        assert_eq!(mnemonic.entropy(), entropy);
+        let parsed = Mnemonic::from(&mnemonic.to_string()).unwrap();
+        assert_eq!(parsed.entropy(), entropy);
+    }
+}
+
 #[test]
 fn test_deterministic_mnemonic() {
     let zeros_entropy: [u8; 32] = [0; 32];
@@ -0,0 +1,243 @@
+//! Shamir secret sharing of the mnemonic entropy, for k-of-n backups.
+//!
+//! Each byte of the entropy is the constant term of a random polynomial of
+//! degree k - 1 over GF(256), and share i holds the polynomials evaluated at
+//! x = i. Any k shares recover the entropy by interpolating at x = 0; fewer
+//! reveal nothing about it.
+//!
+//! A share is written as BIP39 words:
+//! `<index> <threshold> <share data as a mnemonic> <checksum>`
+//! where the checksum word covers the whole share.
+
+use super::mnemonic::{self, Mnemonic};
+use anyhow::{bail, Context, Result};
+use rand::{rngs::OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use std::fmt::Display;
+
+/// One share of a split mnemonic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Share {
+    /// the x coordinate, from 1
+    pub index: u8,
+    /// how many shares are needed to combine
+    pub threshold: u8,
+    /// the y coordinates, one per byte of entropy
+    pub data: Vec<u8>,
+}
+
+impl Share {
+    fn checksum_word(&self) -> &'static str {
+        let mut h = Sha256::new();
+        h.update([self.index, self.threshold]);
+        h.update(&self.data);
+        let digest = h.finalize();
+        let idx = u16::from_be_bytes([digest[0], digest[1]]) as usize & 0x7FF;
+        mnemonic::word_at(idx).expect("the word list has 2048 words, the index is 11 bits")
+    }
+
+    /// Parse a share from its words. Fails on a typo, since the checksums will not match.
+    pub fn from_words(s: &str) -> Result<Self> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        if words.len() < 4 {
+            bail!("a share has the index, the threshold, the data words and a checksum word");
+        }
+        let number = |w: &str| -> Result<u8> {
+            let i = mnemonic::word_index(w).context(format!("unknown word {}", w))?;
+            u8::try_from(i).ok().context("invalid share header")
+        };
+        let index = number(words[0])?;
+        let threshold = number(words[1])?;
+        let data = Mnemonic::from(&words[2..words.len() - 1].join(" "))
+            .context("the data words of the share are invalid")?
+            .entropy();
+
+        let share = Share {
+            index,
+            threshold,
+            data,
+        };
+        if share.checksum_word() != words[words.len() - 1] {
+            bail!("share checksum failed, check the words");
+        }
+        if index == 0 || threshold == 0 {
+            bail!("invalid share header");
+        }
+        Ok(share)
+    }
+
+    /// The words of the share.
+    pub fn to_words(&self) -> String {
+        let data = Mnemonic::new(&self.data)
+            .expect("the data of a share has the length of the mnemonic entropy");
+        // the header bytes are below 256, and the word list has 2048 words
+        format!(
+            "{} {} {} {}",
+            mnemonic::word_at(self.index as usize).expect("the index is a u8"),
+            mnemonic::word_at(self.threshold as usize).expect("the threshold is a u8"),
+            data,
+            self.checksum_word()
+        )
+    }
+}
+
+impl Display for Share {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_words())
+    }
+}
+
+/// Split a mnemonic into `shares` shares, any `threshold` of which recover it.
+pub fn split(mnemonic: &Mnemonic, threshold: u8, shares: u8) -> Result<Vec<Share>> {
+    if threshold < 2 || threshold > shares {
+        bail!("the threshold must be at least 2, and at most the number of shares");
+    }
+    let secret = mnemonic.entropy();
+
+    let mut list: Vec<Share> = (1..=shares)
+        .map(|index| Share {
+            index,
+            threshold,
+            data: Vec::with_capacity(secret.len()),
+        })
+        .collect();
+
+    let mut coefficients = vec![0u8; threshold as usize];
+    for byte in secret {
+        coefficients[0] = byte;
+        OsRng.fill_bytes(&mut coefficients[1..]);
+        for s in list.iter_mut() {
+            s.data.push(eval(&coefficients, s.index));
+        }
+    }
+    Ok(list)
+}
+
+/// Recover the mnemonic from at least `threshold` shares.
+pub fn combine(shares: &[Share]) -> Result<Mnemonic> {
+    let first = shares.first().context("no shares")?;
+    let threshold = first.threshold as usize;
+
+    let mut used: Vec<&Share> = vec![];
+    for s in shares {
+        if s.threshold != first.threshold || s.data.len() != first.data.len() {
+            bail!("the shares are not from the same split");
+        }
+        if let Some(other) = used.iter().find(|u| u.index == s.index) {
+            if other.data != s.data {
+                bail!("two different shares have the index {}", s.index);
+            }
+            continue;
+        }
+        used.push(s);
+    }
+    if used.len() < threshold {
+        bail!(
+            "{} shares are needed, only {} were given",
+            threshold,
+            used.len()
+        );
+    }
+    let used = &used[..threshold];
+
+    let secret: Vec<u8> = (0..first.data.len())
+        .map(|i| {
+            // Lagrange interpolation at x = 0
+            used.iter().fold(0u8, |acc, s| {
+                let basis = used
+                    .iter()
+                    .filter(|o| o.index != s.index)
+                    .fold(1u8, |b, o| gf_mul(b, gf_div(o.index, o.index ^ s.index)));
+                acc ^ gf_mul(s.data[i], basis)
+            })
+        })
+        .collect();
+
+    Mnemonic::new(&secret)
+}
+
+/// Evaluate the polynomial at x, with Horner's method.
+fn eval(coefficients: &[u8], x: u8) -> u8 {
+    coefficients
+        .iter()
+        .rev()
+        .fold(0u8, |acc, c| gf_mul(acc, x) ^ c)
+}
+
+/// Multiplication in GF(256), with the AES polynomial.
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut p = 0u8;
+    while b != 0 {
+        if b & 1 != 0 {
+            p ^= a;
+        }
+        let carry = a & 0x80 != 0;
+        a <<= 1;
+        if carry {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    p
+}
+
+/// The inverse is a^254, since a^255 = 1.
+fn gf_inv(a: u8) -> u8 {
+    let mut result = 1u8;
+    let mut base = a;
+    let mut exp = 254u8;
+    while exp > 0 {
+        if exp & 1 != 0 {
+            result = gf_mul(result, base);
+        }
+        base = gf_mul(base, base);
+        exp >>= 1;
+    }
+    result
+}
+
+fn gf_div(a: u8, b: u8) -> u8 {
+    gf_mul(a, gf_inv(b))
+}
+
+#[test]
+fn split_and_combine() {
+    let alice = Mnemonic::from("talent sunset lizard pill fame nuclear spy noodle basket okay critic grow sleep legend hurry pitch blanket clerk impose rough degree sock insane purse").unwrap();
+
+    let shares = split(&alice, 3, 5).unwrap();
+    assert_eq!(shares.len(), 5);
+
+    // any 3 of them
+    for picked in [[0, 1, 2], [4, 2, 0], [1, 3, 4]] {
+        let some: Vec<Share> = picked.iter().map(|&i| shares[i].clone()).collect();
+        assert_eq!(combine(&some).unwrap().to_string(), alice.to_string());
+    }
+
+    // the same keys are derived from the combined mnemonic
+    let restored = combine(&shares[2..]).unwrap();
+    let keys = crate::account_keys::get_keys_from_mnem(restored.to_string()).unwrap();
+    let expected = crate::account_keys::get_keys_from_mnem(alice.to_string()).unwrap();
+    assert_eq!(keys.child_0_owner.account, expected.child_0_owner.account);
+    assert_eq!(
+        keys.child_5_executor.pri_key.to_bytes(),
+        expected.child_5_executor.pri_key.to_bytes()
+    );
+
+    // 2 are not enough
+    assert!(combine(&shares[..2]).is_err());
+
+    // the words round trip, and a typo is caught
+    let words = shares[3].to_words();
+    assert_eq!(Share::from_words(&words).unwrap(), shares[3]);
+    let typo = words.replacen(words.split(' ').nth(5).unwrap(), "zoo", 1);
+    assert!(Share::from_words(&typo).is_err());
+}
+
+#[test]
+fn gf_arithmetic() {
+    for a in 1..=255u8 {
+        assert_eq!(gf_mul(a, gf_inv(a)), 1);
+    }
+    // from the AES specification
+    assert_eq!(gf_mul(0x57, 0x83), 0xc1);
+}
@@ -21,6 +21,11 @@ use diem_types::{account_address::AccountAddress, transaction::authenticator::Au
 use rand::{rngs::OsRng, Rng};
 use std::collections::HashMap;
 
+//////// 0L ////////
+/// The salt of the 0L seed derivation. An optional passphrase is appended to
+/// it, so that without a passphrase the seed is the same as always.
+pub const OL_SEED_SALT: &str = "0L";
+
 /// WalletLibrary contains all the information needed to recreate a particular wallet
 pub struct WalletLibrary {
     mnemonic: Mnemonic,
@@ -34,15 +39,29 @@ impl WalletLibrary {
     /// empty WalletLibrary from that Mnemonic
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
+        Self::new_with_passphrase("")
+    }
+
+    //////// 0L ////////
+    /// Generates a new Mnemonic, with a passphrase to derive the keys
+    pub fn new_with_passphrase(passphrase: &str) -> Self {
         let mut rng = OsRng;
         let data: [u8; 32] = rng.gen();
         let mnemonic = Mnemonic::new(&data).unwrap();
-        Self::new_from_mnemonic(mnemonic)
+        Self::new_from_mnemonic_with_passphrase(mnemonic, passphrase)
     }
 
     /// Constructor that instantiates a new WalletLibrary from Mnemonic
     pub fn new_from_mnemonic(mnemonic: Mnemonic) -> Self {
-        let seed = Seed::new(&mnemonic, "0L"); //////// 0L ////////
+        Self::new_from_mnemonic_with_passphrase(mnemonic, "")
+    }
+
+    //////// 0L ////////
+    /// The passphrase is a second factor (a "25th word"): the same mnemonic
+    /// with another passphrase derives unrelated keys. An empty passphrase
+    /// derives the keys of the mnemonic alone.
+    pub fn new_from_mnemonic_with_passphrase(mnemonic: Mnemonic, passphrase: &str) -> Self {
+        let seed = Seed::new(&mnemonic, &format!("{}{}", OL_SEED_SALT, passphrase));
         WalletLibrary {
             mnemonic,
             key_factory: KeyFactory::new(&seed).unwrap(),
@@ -51,6 +70,18 @@ impl WalletLibrary {
         }
     }
 
+    //////// 0L ////////
+    /// A copy of this wallet with no addresses generated yet. It keeps the
+    /// seed, so a passphrase wallet stays one.
+    pub fn fresh_copy(&self) -> Self {
+        WalletLibrary {
+            mnemonic: self.mnemonic.clone(),
+            key_factory: self.key_factory.clone(),
+            addr_map: HashMap::new(),
+            key_leaf: ChildNumber(0),
+        }
+    }
+
     /// Function that returns the string representation of the WalletLibrary Mnemonic
     /// NOTE: This is not secure, and in general the mnemonic should be stored in encrypted format
     pub fn mnemonic(&self) -> String {
@@ -10,9 +10,9 @@ use libra_types::exports::{AccountAddress, AuthenticationKey};
 use std::path::PathBuf;
 
 /// Generates keys from WalletLibrary, updates a MinerConfig
-pub fn keygen() -> (AuthenticationKey, AccountAddress, WalletLibrary, String) {
+pub fn keygen(passphrase: &str) -> (AuthenticationKey, AccountAddress, WalletLibrary, String) {
     // Generate new keys
-    let mut wallet = WalletLibrary::new();
+    let mut wallet = WalletLibrary::new_with_passphrase(passphrase);
     let mnemonic_string = wallet.mnemonic();
     // NOTE: Authkey uses the child number 0 by default
     let (auth_key, _) = wallet.new_address().expect("Could not generate address");
@@ -21,11 +21,16 @@ pub fn keygen() -> (AuthenticationKey, AccountAddress, WalletLibrary, String) {
 }
 
 /// Runs the key generation process.
-pub async fn run(mnemonic: Option<String>, output_dir: Option<PathBuf>) -> Result<String> {
+pub async fn run(
+    mnemonic: Option<String>,
+    output_dir: Option<PathBuf>,
+    passphrase: &str,
+) -> Result<String> {
     let private_key = if let Some(mnemonic) = mnemonic {
-        let (_, account_address, wallet_lib) = load_keys::get_account_from_mnem(mnemonic.clone())?;
+        let (_, account_address, wallet_lib) =
+            load_keys::get_account_from_mnem_with_passphrase(mnemonic.clone(), passphrase)?;
 
-        refresh_validator_files(Some(mnemonic), output_dir, false)?;
+        refresh_validator_files(Some(mnemonic), output_dir, false, passphrase)?;
 
         Ed25519PrivateKey::try_from(
             wallet_lib
@@ -34,7 +39,7 @@ pub async fn run(mnemonic: Option<String>, output_dir: Option<PathBuf>) -> Resul
                 .as_ref(),
         )?
     } else {
-        let (_, _, private_identity, _) = validator_keygen(output_dir)?;
+        let (_, _, private_identity, _) = validator_keygen(output_dir, passphrase)?;
         private_identity.account_private_key
     };
 
@@ -66,9 +71,13 @@ mod tests {
     async fn save_val_keys_from_mnemonic() -> Result<()> {
         let this_dir: PathBuf = env!("CARGO_MANIFEST_DIR").parse()?;
         let output_dir = this_dir.join("temp_two");
-        let result = run(Some(ALICE_MNEMONIC.to_string()), Some(output_dir.clone()))
-            .await
-            .unwrap();
+        let result = run(
+            Some(ALICE_MNEMONIC.to_string()),
+            Some(output_dir.clone()),
+            "",
+        )
+        .await
+        .unwrap();
 
         let result = result.split('\n').collect::<Vec<_>>();
 
@@ -107,7 +116,7 @@ mod tests {
         let this_dir: PathBuf = env!("CARGO_MANIFEST_DIR").parse()?;
 
         let output_dir = this_dir.join("temp");
-        let result = run(None, Some(output_dir.clone())).await.unwrap();
+        let result = run(None, Some(output_dir.clone()), "").await.unwrap();
         let result = result.split('\n').collect::<Vec<_>>();
 
         let private_key = hex::decode(result[1].replace("Private key: ", "")).unwrap();
@@ -6,7 +6,8 @@
 
 use crate::{
     account_keys::{
-        get_keys_from_mnem, get_keys_from_prompt, get_ol_legacy_address, legacy_keygen, KeyChain,
+        get_keys_from_mnem_with_passphrase, get_keys_from_prompt, get_ol_legacy_address,
+        legacy_keygen, KeyChain,
     },
     utils::{
         check_if_file_exists, create_dir_if_not_exist, dir_default_to_current, prompt_yes, to_yaml,
@@ -32,7 +33,7 @@ const USER_FILE: &str = "danger-user-private-keys.yaml";
 
 // Generate new keys for user
 pub fn user_keygen(output_opt: Option<PathBuf>) -> anyhow::Result<()> {
-    let user_keys = legacy_keygen(true)?;
+    let user_keys = legacy_keygen(true, "")?;
 
     if let Some(dir) = output_opt {
         if prompt_yes("Saving keys locally is VERY DANGEROUS, do you know what you are doing?") {
@@ -45,9 +46,10 @@ pub fn user_keygen(output_opt: Option<PathBuf>) -> anyhow::Result<()> {
 // NOTE: Devs: this is copied from diem_genesis::keys::generate_key_objects()  and modified to use our legacy keygen process.
 pub fn validator_keygen(
     output_opt: Option<PathBuf>,
+    passphrase: &str,
 ) -> anyhow::Result<(IdentityBlob, IdentityBlob, PrivateIdentity, PublicIdentity)> {
     // this is the only moment the validators will see the mnemonic
-    let legacy_keys = legacy_keygen(true)?;
+    let legacy_keys = legacy_keygen(true, passphrase)?;
 
     let (validator_blob, vfn_blob, private_identity, public_identity) =
         generate_key_objects_from_legacy(&legacy_keys)?;
@@ -68,6 +70,7 @@ pub fn refresh_validator_files(
     mnem: Option<String>,
     output_opt: Option<PathBuf>,
     keep_legacy_addr: bool,
+    passphrase: &str,
 ) -> anyhow::Result<(
     IdentityBlob,
     IdentityBlob,
@@ -76,7 +79,7 @@ pub fn refresh_validator_files(
     KeyChain,
 )> {
     let (validator_blob, vfn_blob, private_identity, public_identity, legacy_keys) =
-        make_validator_keys(mnem, keep_legacy_addr)?;
+        make_validator_keys(mnem, keep_legacy_addr, passphrase)?;
 
     save_val_files(
         output_opt,
@@ -99,6 +102,7 @@ pub fn refresh_validator_files(
 pub fn make_validator_keys(
     mnem: Option<String>,
     keep_legacy_addr: bool,
+    passphrase: &str,
 ) -> anyhow::Result<(
     IdentityBlob,
     IdentityBlob,
@@ -107,9 +111,12 @@ pub fn make_validator_keys(
     KeyChain,
 )> {
     let mut legacy_keys = if let Some(m) = mnem {
-        get_keys_from_mnem(m)?
-    } else {
+        get_keys_from_mnem_with_passphrase(m, passphrase)?
+    } else if passphrase.is_empty() {
         get_keys_from_prompt()?
+    } else {
+        let (_, _, wallet) = crate::load_keys::get_account_from_prompt();
+        get_keys_from_mnem_with_passphrase(wallet.mnemonic(), passphrase)?
     };
 
     if keep_legacy_addr {
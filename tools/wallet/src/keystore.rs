@@ -0,0 +1,331 @@
+//! Encrypted keystore for the private keys of the CLI profiles.
+//! Each key is sealed with AES-256-GCM, under a key derived from the user's
+//! password with scrypt, and saved in keystore/ next to libra-cli-config.yaml.
+
+use crate::utils::write_to_user_only_file;
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use anyhow::{anyhow, bail, Context};
+use diem_crypto::{
+    ed25519::Ed25519PrivateKey, PrivateKey, ValidCryptoMaterial, ValidCryptoMaterialStringExt,
+};
+use diem_types::chain_id::NamedChain;
+use libra_types::{
+    core_types::{app_cfg::AppCfg, mode_ol::MODE_0L},
+    exports::{AccountAddress, AuthenticationKey},
+};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+use zeroize::Zeroizing;
+
+/// The directory of the keystore files, in the config directory.
+pub const KEYSTORE_DIR: &str = "keystore";
+
+const KEYSTORE_VERSION: u8 = 1;
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+/// scrypt cost, N = 2^15, which takes a fraction of a second
+const SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+/// bounds of the scrypt parameters read from a keystore file, so a modified
+/// file cannot make the key derivation take all the memory or time
+const SCRYPT_MAX_LOG_N: u8 = 18;
+const SCRYPT_MAX_P: u32 = 16;
+/// scrypt uses 128 * r * 2^log_n bytes, 256 MiB at log_n 18 and r 8
+const SCRYPT_MAX_MEMORY: u64 = 256 * 1024 * 1024;
+
+/// Parameters of the password key derivation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KdfParams {
+    /// only "scrypt" for now
+    pub name: String,
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+    /// hex
+    pub salt: String,
+}
+
+/// A sealed private key, as saved in the keystore.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedKey {
+    pub version: u8,
+    /// the account this key signs for
+    pub account: AccountAddress,
+    /// the auth key of the sealed private key, to check it without the password
+    pub auth_key: AuthenticationKey,
+    pub kdf: KdfParams,
+    /// only "aes-256-gcm" for now
+    pub cipher: String,
+    /// hex
+    pub nonce: String,
+    /// hex, includes the authentication tag
+    pub ciphertext: String,
+}
+
+impl EncryptedKey {
+    /// Seal a private key with a password.
+    pub fn seal(
+        key: &Ed25519PrivateKey,
+        account: AccountAddress,
+        password: &str,
+    ) -> anyhow::Result<Self> {
+        if password.is_empty() {
+            bail!("the keystore password cannot be empty");
+        }
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+
+        let kdf = KdfParams {
+            name: "scrypt".to_owned(),
+            log_n: SCRYPT_LOG_N,
+            r: SCRYPT_R,
+            p: SCRYPT_P,
+            salt: hex::encode(salt),
+        };
+        let cipher = Aes256Gcm::new_from_slice(derive_key(&kdf, password)?.as_slice())
+            .map_err(|_| anyhow!("invalid key length"))?;
+        let ciphertext = cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Zeroizing::new(key.to_bytes()).as_slice(),
+            )
+            .map_err(|_| anyhow!("could not encrypt the private key"))?;
+
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            account,
+            auth_key: AuthenticationKey::ed25519(&key.public_key()),
+            kdf,
+            cipher: "aes-256-gcm".to_owned(),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+
+    /// Unseal the private key. Fails on a wrong password, or if the file was modified.
+    pub fn open(&self, password: &str) -> anyhow::Result<Ed25519PrivateKey> {
+        if self.version != KEYSTORE_VERSION || self.cipher != "aes-256-gcm" {
+            bail!(
+                "unsupported keystore file, version {} cipher {}",
+                self.version,
+                self.cipher
+            );
+        }
+        let cipher = Aes256Gcm::new_from_slice(derive_key(&self.kdf, password)?.as_slice())
+            .map_err(|_| anyhow!("invalid key length"))?;
+        let nonce = hex::decode(&self.nonce).context("invalid nonce")?;
+        if nonce.len() != NONCE_LEN {
+            bail!("invalid nonce length");
+        }
+        let plaintext = cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                hex::decode(&self.ciphertext)?.as_slice(),
+            )
+            .map(Zeroizing::new)
+            .map_err(|_| anyhow!("wrong password, or the keystore file is corrupted"))?;
+
+        let key = Ed25519PrivateKey::try_from(plaintext.as_slice())
+            .map_err(|e| anyhow!("the keystore does not hold a valid private key: {}", e))?;
+        if AuthenticationKey::ed25519(&key.public_key()) != self.auth_key {
+            bail!("the private key in the keystore does not match its auth key");
+        }
+        Ok(key)
+    }
+
+    /// Seal the same key with a new password.
+    pub fn change_password(&self, old: &str, new: &str) -> anyhow::Result<Self> {
+        let key = self.open(old)?;
+        Self::seal(&key, self.account, new)
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // the file is encrypted, but still only for the user to read
+        write_to_user_only_file(
+            path,
+            &path.display().to_string(),
+            serde_json::to_string_pretty(self)?.as_bytes(),
+        )
+    }
+
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let s = fs::read_to_string(path)
+            .context(format!("could not read keystore file {}", path.display()))?;
+        Ok(serde_json::from_str(&s)?)
+    }
+}
+
+fn derive_key(kdf: &KdfParams, password: &str) -> anyhow::Result<Zeroizing<[u8; KEY_LEN]>> {
+    if kdf.name != "scrypt" {
+        bail!("unsupported key derivation {}", kdf.name);
+    }
+    if !(1..=SCRYPT_MAX_LOG_N).contains(&kdf.log_n)
+        || kdf.r == 0
+        || 128 * kdf.r as u64 * (1u64 << kdf.log_n) > SCRYPT_MAX_MEMORY
+        || !(1..=SCRYPT_MAX_P).contains(&kdf.p)
+    {
+        bail!(
+            "scrypt parameters out of range, log_n {} r {} p {}",
+            kdf.log_n,
+            kdf.r,
+            kdf.p
+        );
+    }
+    let params = scrypt::Params::new(kdf.log_n, kdf.r, kdf.p, KEY_LEN)
+        .map_err(|e| anyhow!("invalid scrypt parameters: {}", e))?;
+    let salt = hex::decode(&kdf.salt).context("invalid salt")?;
+    let mut out = Zeroizing::new([0u8; KEY_LEN]);
+    scrypt::scrypt(password.as_bytes(), &salt, &params, out.as_mut_slice())
+        .map_err(|e| anyhow!("could not derive the key: {}", e))?;
+    Ok(out)
+}
+
+/// The directory of libra-cli-config.yaml, which has the keystore.
+pub fn config_dir(config_file: &Path) -> &Path {
+    config_file.parent().unwrap_or_else(|| Path::new(""))
+}
+
+/// Where the key of an account is stored, given the config directory.
+pub fn keystore_path(config_dir: &Path, account: AccountAddress) -> PathBuf {
+    config_dir
+        .join(KEYSTORE_DIR)
+        .join(format!("{}.json", account.to_hex()))
+}
+
+/// Asks for the keystore password. In testing mode it can be set with $KEYSTORE_PASSWORD.
+pub fn prompt_password(prompt: &str) -> anyhow::Result<Zeroizing<String>> {
+    if *MODE_0L == NamedChain::TESTING {
+        if let Ok(p) = env::var("KEYSTORE_PASSWORD") {
+            println!(
+                "Debugging mode, using keystore password from env variable, $KEYSTORE_PASSWORD"
+            );
+            return Ok(Zeroizing::new(p));
+        }
+    }
+    println!("{}", prompt);
+    rpassword::read_password_from_tty(Some("\u{1F511} "))
+        .map(Zeroizing::new)
+        .context("could not read the password from the prompt")
+}
+
+/// Asks for a new password twice.
+pub fn prompt_new_password() -> anyhow::Result<Zeroizing<String>> {
+    let p = prompt_password("Enter a new keystore password:")?;
+    if *MODE_0L != NamedChain::TESTING || env::var("KEYSTORE_PASSWORD").is_err() {
+        let again = prompt_password("Enter it again:")?;
+        if p != again {
+            bail!("the passwords do not match");
+        }
+    }
+    Ok(p)
+}
+
+/// Unlock the key of a profile, if it is in the keystore next to the config file.
+pub fn unlock_profile_key(
+    config_file: &Path,
+    app_cfg: &AppCfg,
+    nickname: Option<String>,
+) -> anyhow::Result<Option<Ed25519PrivateKey>> {
+    let profile = app_cfg.get_profile(nickname)?;
+    let path = keystore_path(config_dir(config_file), profile.account);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let sealed = EncryptedKey::read(&path)?;
+    let password = prompt_password(&format!(
+        "Enter the keystore password for {}:",
+        profile.account
+    ))?;
+    Ok(Some(sealed.open(&password)?))
+}
+
+/// Seal a key into the keystore of a config directory. Returns the file path.
+pub fn import_key(
+    config_dir: &Path,
+    key: &Ed25519PrivateKey,
+    account: AccountAddress,
+    password: &str,
+) -> anyhow::Result<PathBuf> {
+    let path = keystore_path(config_dir, account);
+    EncryptedKey::seal(key, account, password)?.save(&path)?;
+    Ok(path)
+}
+
+/// The private key as an encoded string, for backups.
+pub fn export_key(path: &Path, password: &str) -> anyhow::Result<String> {
+    let key = EncryptedKey::read(path)?.open(password)?;
+    Ok(key.to_encoded_string()?)
+}
+
+#[test]
+fn seal_and_open() {
+    use diem_crypto::Uniform;
+    let key = Ed25519PrivateKey::generate(&mut rand::rngs::OsRng);
+    let sealed = EncryptedKey::seal(&key, AccountAddress::ONE, "correct horse").unwrap();
+
+    assert_eq!(
+        sealed.open("correct horse").unwrap().to_bytes(),
+        key.to_bytes()
+    );
+    assert!(sealed.open("wrong horse").is_err());
+
+    // a modified file cannot ask for a huge key derivation
+    let mut costly = sealed.clone();
+    costly.kdf.log_n = 40;
+    assert!(costly.open("correct horse").is_err());
+    let mut costly = sealed.clone();
+    costly.kdf.r = u32::MAX;
+    assert!(costly.open("correct horse").is_err());
+    // each bound alone is fine, but together they would take 1 GiB
+    let mut costly = sealed.clone();
+    costly.kdf.log_n = SCRYPT_MAX_LOG_N;
+    costly.kdf.r = 32;
+    assert!(costly.open("correct horse").is_err());
+
+    // tampering is detected by the AEAD tag
+    let mut tampered = sealed.clone();
+    let flipped = if tampered.ciphertext.starts_with('0') {
+        "1"
+    } else {
+        "0"
+    };
+    tampered.ciphertext.replace_range(0..1, flipped);
+    assert!(tampered.open("correct horse").is_err());
+
+    let changed = sealed.change_password("correct horse", "battery").unwrap();
+    assert!(changed.open("correct horse").is_err());
+    assert_eq!(changed.open("battery").unwrap().to_bytes(), key.to_bytes());
+
+    // round trip through the file
+    let d = diem_temppath::TempPath::new();
+    d.create_as_dir().unwrap();
+    let path = import_key(d.path(), &key, AccountAddress::ONE, "battery").unwrap();
+    assert_eq!(path, keystore_path(d.path(), AccountAddress::ONE));
+    // found next to the config file, wherever it is
+    let config_file = d
+        .path()
+        .join(libra_types::core_types::app_cfg::CONFIG_FILE_NAME);
+    assert_eq!(
+        keystore_path(config_dir(&config_file), AccountAddress::ONE),
+        path
+    );
+    assert_eq!(
+        export_key(&path, "battery").unwrap(),
+        key.to_encoded_string().unwrap()
+    );
+}
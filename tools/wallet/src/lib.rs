@@ -5,7 +5,10 @@ pub mod account_keys;
 pub mod core;
 pub mod key_gen;
 pub mod keys;
+pub mod keystore;
 pub mod load_keys;
+pub mod message;
+pub mod recover;
 pub mod utils;
 pub mod validator_files;
 pub mod wallet_cli;
@@ -11,7 +11,18 @@ use std::{env, process::exit};
 pub fn get_account_from_mnem(
     mnemonic_string: String,
 ) -> Result<(AuthenticationKey, AccountAddress, WalletLibrary), anyhow::Error> {
-    let mut wallet = WalletLibrary::new_from_mnemonic(Mnemonic::from(mnemonic_string.trim())?);
+    get_account_from_mnem_with_passphrase(mnemonic_string, "")
+}
+
+/// Get authkey and account from mnemonic and the optional passphrase
+pub fn get_account_from_mnem_with_passphrase(
+    mnemonic_string: String,
+    passphrase: &str,
+) -> Result<(AuthenticationKey, AccountAddress, WalletLibrary), anyhow::Error> {
+    let mut wallet = WalletLibrary::new_from_mnemonic_with_passphrase(
+        Mnemonic::from(mnemonic_string.trim())?,
+        passphrase,
+    );
     let (auth_key, _) = wallet.new_address()?;
     let account = auth_key.derived_address();
     Ok((auth_key, account, wallet))
@@ -21,11 +32,35 @@ pub fn get_account_from_mnem(
 pub fn get_account_from_wallet(
     wallet: &WalletLibrary,
 ) -> Result<(AuthenticationKey, AccountAddress, WalletLibrary), anyhow::Error> {
-    get_account_from_mnem(wallet.mnemonic())
+    // keep the wallet's seed, the mnemonic alone loses the passphrase
+    let mut wallet = wallet.fresh_copy();
+    let (auth_key, _) = wallet.new_address()?;
+    let account = auth_key.derived_address();
+    Ok((auth_key, account, wallet))
+}
+
+/// Prompts user to type the mnemonic passphrase securely.
+pub fn prompt_passphrase(confirm: bool) -> anyhow::Result<String> {
+    println!("Enter your mnemonic passphrase:");
+    let p = rpassword::read_password_from_tty(Some("\u{1F511} "))?;
+    if confirm {
+        println!("Enter it again:");
+        if p != rpassword::read_password_from_tty(Some("\u{1F511} "))? {
+            anyhow::bail!("the passphrases do not match");
+        }
+    }
+    Ok(p)
 }
 
 /// Prompts user to type mnemonic securely.
 pub fn get_account_from_prompt() -> (AuthenticationKey, AccountAddress, WalletLibrary) {
+    get_account_from_prompt_with_passphrase("")
+}
+
+/// Prompts user to type mnemonic securely, and derives with the optional passphrase.
+pub fn get_account_from_prompt_with_passphrase(
+    passphrase: &str,
+) -> (AuthenticationKey, AccountAddress, WalletLibrary) {
     println!("Enter your 0L mnemonic:");
 
     let test_env_mnem = env::var("MNEM");
@@ -47,7 +82,7 @@ pub fn get_account_from_prompt() -> (AuthenticationKey, AccountAddress, WalletLi
         },
     };
 
-    match get_account_from_mnem(mnem) {
+    match get_account_from_mnem_with_passphrase(mnem, passphrase) {
         Ok(a) => a,
         Err(e) => {
             println!(
@@ -0,0 +1,148 @@
+//! Off-chain messages, to prove control of an account without sending a transaction.
+//!
+//! The signed bytes are sha3(MESSAGE_DOMAIN) || bcs(address, message), in the
+//! same way Diem prefixes transactions with a domain separation hash. So a
+//! message signature can never be replayed as a transaction, or for another
+//! account.
+
+use crate::core::key_factory::ExtendedPrivKey;
+use anyhow::{bail, Context};
+use diem_crypto::{
+    ed25519::{Ed25519PublicKey, Ed25519Signature},
+    HashValue, Signature,
+};
+use diem_sdk::rest_client::Client;
+use libra_types::{
+    exports::{AccountAddress, AuthenticationKey},
+    type_extensions::client_ext::ClientExt,
+};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// The domain separator of off-chain messages.
+pub const MESSAGE_DOMAIN: &[u8] = b"0L::OffChainMessage::v1";
+
+/// A message, its signature, and who signed it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedMessage {
+    /// the account the signer claims to control
+    pub address: AccountAddress,
+    pub public_key: Ed25519PublicKey,
+    pub signature: Ed25519Signature,
+    pub message: String,
+}
+
+/// The bytes which are signed, for a message from an account.
+pub fn signing_bytes(address: AccountAddress, message: &str) -> anyhow::Result<Vec<u8>> {
+    let mut bytes = HashValue::sha3_256_of(MESSAGE_DOMAIN).to_vec();
+    bytes.extend(bcs::to_bytes(&(address, message))?);
+    Ok(bytes)
+}
+
+impl SignedMessage {
+    /// Sign a message for an account. The address is not always derived from
+    /// the key, since the account may have rotated its key.
+    pub fn sign(
+        key: &ExtendedPrivKey,
+        address: AccountAddress,
+        message: &str,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            address,
+            public_key: key.get_public(),
+            signature: key.sign_arbitrary_message(&signing_bytes(address, message)?),
+            message: message.to_owned(),
+        })
+    }
+
+    /// Check the signature only. This does not prove the key controls the account.
+    pub fn verify_signature(&self) -> anyhow::Result<()> {
+        self.signature
+            .verify_arbitrary_msg(
+                &signing_bytes(self.address, &self.message)?,
+                &self.public_key,
+            )
+            .context("the signature is not valid for this message and address")
+    }
+
+    /// Check the signature, and that the key is the current key of the account on chain.
+    pub async fn verify_on_chain(&self, client: &Client) -> anyhow::Result<()> {
+        self.verify_signature()?;
+        let auth_key = AuthenticationKey::ed25519(&self.public_key);
+
+        // a rotated key is found in the originating address table, otherwise
+        // the address derived from the key is returned
+        let originating = client
+            .lookup_originating_address(auth_key)
+            .await
+            .context("could not look up the originating address of the key")?;
+        if originating != self.address {
+            bail!(
+                "the key belongs to account {}, not {}",
+                originating,
+                self.address
+            );
+        }
+
+        // the key may also have been rotated away from
+        let current = client
+            .get_account(self.address)
+            .await
+            .context("could not get the account, is it on chain?")?
+            .into_inner()
+            .authentication_key;
+        if current != auth_key {
+            bail!(
+                "the key is not the current key of account {}, the auth key is now {}",
+                self.address,
+                current
+            );
+        }
+        Ok(())
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let s = fs::read_to_string(path)
+            .context(format!("could not read signed message {}", path.display()))?;
+        Ok(serde_json::from_str(&s)?)
+    }
+}
+
+#[test]
+fn sign_and_verify_message() {
+    use crate::{core::key_factory::ChildNumber, load_keys::get_account_from_mnem};
+
+    let alice_mnem = "talent sunset lizard pill fame nuclear spy noodle basket okay critic grow sleep legend hurry pitch blanket clerk impose rough degree sock insane purse";
+    let (_, account, wallet) = get_account_from_mnem(alice_mnem.to_owned()).unwrap();
+    let key = wallet
+        .get_key_factory()
+        .private_child(ChildNumber::new(0))
+        .unwrap();
+
+    let signed = SignedMessage::sign(&key, account, "I am alice").unwrap();
+    signed.verify_signature().unwrap();
+
+    // round trip through json
+    let json = serde_json::to_string(&signed).unwrap();
+    let read: SignedMessage = serde_json::from_str(&json).unwrap();
+    read.verify_signature().unwrap();
+
+    let mut other_message = signed.clone();
+    other_message.message = "I am bob".to_owned();
+    assert!(other_message.verify_signature().is_err());
+
+    let mut other_account = signed.clone();
+    other_account.address = AccountAddress::ONE;
+    assert!(other_account.verify_signature().is_err());
+
+    // not the bytes of a raw transaction, nor of the bare message
+    assert!(signed
+        .signature
+        .verify_arbitrary_msg(b"I am alice", &signed.public_key)
+        .is_err());
+}
@@ -0,0 +1,244 @@
+//! Recover a mnemonic with a mistyped or a missing word.
+//!
+//! Words which are not in the word list are replaced with the nearest words
+//! by edit distance, and one missing word is tried with every word of the
+//! list. Only the phrases with a valid checksum are kept. The checksum of a
+//! 12 word mnemonic is only 4 bits, so there can be many candidates; checking
+//! which of them has an account on chain narrows them down.
+
+use crate::{
+    core::mnemonic::{self, Mnemonic},
+    load_keys,
+};
+use anyhow::bail;
+use diem_sdk::rest_client::{error::RestError, Client};
+use libra_types::{exports::AccountAddress, type_extensions::client_ext::ClientExt};
+use std::collections::BTreeSet;
+
+/// Words further than this from a typo are not tried.
+pub const MAX_EDIT_DISTANCE: usize = 2;
+
+/// The most phrases to try, before giving up.
+pub const MAX_COMBINATIONS: usize = 1_000_000;
+
+/// The placeholder for a missing word at a known position.
+pub const MISSING_WORD: &str = "?";
+
+const WORD_LIST_LEN: usize = 2048;
+const MNEMONIC_LENGTHS: [usize; 5] = [12, 15, 18, 21, 24];
+
+/// Levenshtein distance between two words.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            row[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(row[j] + 1);
+        }
+        prev = row;
+    }
+    prev[b.len()]
+}
+
+fn all_words() -> impl Iterator<Item = &'static str> {
+    (0..WORD_LIST_LEN).filter_map(mnemonic::word_at)
+}
+
+/// The word of the list, if it is one.
+fn list_word(word: &str) -> Option<&'static str> {
+    mnemonic::word_index(word).and_then(mnemonic::word_at)
+}
+
+/// The words of the list nearest to a typo, within MAX_EDIT_DISTANCE.
+pub fn near_words(word: &str) -> Vec<&'static str> {
+    let scored: Vec<(usize, &'static str)> = all_words()
+        .map(|w| (edit_distance(word, w), w))
+        .filter(|(d, _)| *d <= MAX_EDIT_DISTANCE)
+        .collect();
+    let best = scored.iter().map(|(d, _)| *d).min().unwrap_or(0);
+    scored
+        .into_iter()
+        .filter(|(d, _)| *d == best)
+        .map(|(_, w)| w)
+        .collect()
+}
+
+/// The words of the list which are one or two edits from a valid word, for a
+/// typo which happens to be another word of the list.
+fn other_near_words(word: &str) -> Vec<&'static str> {
+    all_words()
+        .filter(|w| *w != word && edit_distance(word, w) <= MAX_EDIT_DISTANCE)
+        .collect()
+}
+
+/// All the phrases with a valid checksum which are near the given one.
+/// Use MISSING_WORD for a missing word at a known position.
+pub fn recover_candidates(phrase: &str) -> anyhow::Result<Vec<Mnemonic>> {
+    let words: Vec<String> = phrase
+        .split_whitespace()
+        .map(|w| w.to_lowercase())
+        .collect();
+    let missing = words.iter().filter(|w| *w == MISSING_WORD).count();
+
+    // the possible layouts of the words, with the missing word in place
+    let layouts: Vec<Vec<String>> = if MNEMONIC_LENGTHS.contains(&words.len()) {
+        vec![words.clone()]
+    } else if MNEMONIC_LENGTHS.contains(&(words.len() + 1)) && missing == 0 {
+        // one word is missing, and we do not know where
+        (0..=words.len())
+            .map(|i| {
+                let mut l = words.clone();
+                l.insert(i, MISSING_WORD.to_owned());
+                l
+            })
+            .collect()
+    } else {
+        bail!(
+            "a mnemonic has 12, 15, 18, 21 or 24 words, this phrase has {}. Only one word can be missing",
+            words.len()
+        );
+    };
+    if missing > 1 {
+        bail!("only one word can be missing");
+    }
+
+    let mut found = BTreeSet::new();
+    for layout in &layouts {
+        let options = layout
+            .iter()
+            .map(|w| {
+                if w == MISSING_WORD {
+                    return Ok(all_words().collect());
+                }
+                if let Some(known) = list_word(w) {
+                    return Ok(vec![known]);
+                }
+                let near = near_words(w);
+                if near.is_empty() {
+                    bail!("no word of the list is near {}", w);
+                }
+                Ok(near)
+            })
+            .collect::<anyhow::Result<Vec<Vec<&str>>>>()?;
+        try_all(&options, &mut found)?;
+    }
+
+    // every word is in the list but the checksum fails, so one of them is a
+    // typo which is also a word
+    if found.is_empty() && missing == 0 && layouts.len() == 1 {
+        let known: Option<Vec<&'static str>> = layouts[0].iter().map(|w| list_word(w)).collect();
+        if let Some(known) = known {
+            for i in 0..known.len() {
+                let options = known
+                    .iter()
+                    .enumerate()
+                    .map(|(j, w)| {
+                        if i == j {
+                            other_near_words(w)
+                        } else {
+                            vec![*w]
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                try_all(&options, &mut found)?;
+            }
+        }
+    }
+
+    found
+        .into_iter()
+        .map(|p: String| Mnemonic::from(&p))
+        .collect()
+}
+
+/// Try every combination of the options for each word.
+fn try_all(options: &[Vec<&str>], found: &mut BTreeSet<String>) -> anyhow::Result<()> {
+    let combinations = options
+        .iter()
+        .try_fold(1usize, |acc, o| acc.checked_mul(o.len()))
+        .filter(|c| *c <= MAX_COMBINATIONS);
+    if combinations.is_none() {
+        bail!("too many phrases to try, fix some of the words first");
+    }
+
+    let mut counters = vec![0usize; options.len()];
+    loop {
+        let phrase = options
+            .iter()
+            .zip(&counters)
+            .map(|(o, &i)| o[i])
+            .collect::<Vec<_>>()
+            .join(" ");
+        if Mnemonic::from(&phrase).is_ok() {
+            found.insert(phrase);
+        }
+
+        // next combination, like an odometer
+        let mut pos = 0;
+        loop {
+            if pos == options.len() {
+                return Ok(());
+            }
+            counters[pos] += 1;
+            if counters[pos] < options[pos].len() {
+                break;
+            }
+            counters[pos] = 0;
+            pos += 1;
+        }
+    }
+}
+
+/// The candidates whose owner account exists on chain, with the account address.
+pub async fn find_on_chain(
+    client: &Client,
+    candidates: &[Mnemonic],
+    passphrase: &str,
+) -> anyhow::Result<Vec<(Mnemonic, AccountAddress)>> {
+    let mut list = vec![];
+    for m in candidates {
+        let (auth_key, _, _) =
+            load_keys::get_account_from_mnem_with_passphrase(m.to_string(), passphrase)?;
+        // finds the account even if its key was rotated to this one. The view
+        // aborts when no account has this key, which is most candidates.
+        let address = match client.lookup_originating_address(auth_key).await {
+            Ok(a) => a,
+            Err(e) if matches!(e.downcast_ref::<RestError>(), Some(RestError::Api(_))) => continue,
+            Err(e) => return Err(e.context("could not look up the originating address")),
+        };
+        list.push((Mnemonic::from(&m.to_string())?, address));
+    }
+    Ok(list)
+}
+
+#[test]
+fn recover_alice() {
+    let alice = "talent sunset lizard pill fame nuclear spy noodle basket okay critic grow sleep legend hurry pitch blanket clerk impose rough degree sock insane purse";
+    let is_alice = |list: &[Mnemonic]| list.iter().any(|m| m.to_string() == alice);
+
+    assert_eq!(edit_distance("talnet", "talent"), 2);
+    assert_eq!(edit_distance("sunset", "sunset"), 0);
+    assert!(near_words("lizzard").contains(&"lizard"));
+
+    // a word which is not in the list
+    let typo = alice.replace("lizard", "lizzard");
+    assert!(is_alice(&recover_candidates(&typo).unwrap()));
+
+    // a missing word at a known position
+    let known = alice.replace("noodle", MISSING_WORD);
+    assert!(is_alice(&recover_candidates(&known).unwrap()));
+
+    // a missing word somewhere
+    let unknown = alice.replace("okay ", "");
+    let list = recover_candidates(&unknown).unwrap();
+    assert!(is_alice(&list));
+    assert!(list.iter().all(|m| Mnemonic::from(&m.to_string()).is_ok()));
+
+    // a typo which is another word of the list
+    let other_word = alice.replace("pill", "pull");
+    assert!(is_alice(&recover_candidates(&other_word).unwrap()));
+
+    assert!(recover_candidates("talent sunset").is_err());
+}
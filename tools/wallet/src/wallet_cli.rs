@@ -1,8 +1,26 @@
-use crate::{account_keys, whoami::who_am_i};
+use crate::{
+    account_keys,
+    core::{key_factory::ChildNumber, mnemonic::Mnemonic, shamir, wallet_library::WalletLibrary},
+    keystore::{self, EncryptedKey},
+    load_keys,
+    message::SignedMessage,
+    recover,
+    utils::write_to_user_only_file,
+    whoami::{add_profiles, user_accounts, who_am_i},
+};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use clap::{Args, Parser, Subcommand};
+use diem_crypto::PrivateKey;
+use diem_sdk::rest_client::Client;
+use libra_types::{
+    core_types::app_cfg::{default_file_path, AppCfg},
+    exports::{AccountAddress, AuthenticationKey},
+    type_extensions::client_ext::ClientExt,
+};
 use std::path::PathBuf;
+use url::Url;
+use zeroize::Zeroizing;
 
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
@@ -24,11 +42,128 @@ enum WalletSub {
         /// Path of the directory to store yaml files
         #[clap(short, long)]
         output_dir: Option<String>,
+
+        /// Prompt for a passphrase which is needed with the mnemonic to derive the keys (a 25th word)
+        #[clap(long)]
+        passphrase: bool,
     },
     /// Use the legacy key derivation scheme
     Legacy,
     /// use mnemonic to see what account keys are generated
     Whoami(WhoamiOpts),
+    /// Encrypted keystore for the private keys of the config profiles
+    #[clap(subcommand)]
+    Keystore(KeystoreSub),
+    /// Split the mnemonic into shares, any `threshold` of which recover it
+    Split {
+        /// the mnemonic to split, otherwise it will be prompted
+        #[clap(short, long)]
+        mnemonic: Option<String>,
+        /// how many shares to make
+        #[clap(short, long)]
+        shares: u8,
+        /// how many shares are needed to recover the mnemonic
+        #[clap(short, long)]
+        threshold: u8,
+        /// optional, directory to save each share in its own file
+        #[clap(short, long)]
+        output_dir: Option<PathBuf>,
+    },
+    /// Sign a message off-chain, to prove control of an account
+    SignMessage {
+        /// the message to sign
+        #[clap(long)]
+        message: String,
+        /// optional, the mnemonic of the account, otherwise it will be prompted
+        #[clap(short, long)]
+        mnemonic: Option<String>,
+        /// prompt for the passphrase of the mnemonic, if it has one
+        #[clap(long)]
+        passphrase: bool,
+        /// optional, the child number of the key, otherwise the owner key 0
+        #[clap(long, default_value_t = 0)]
+        index: u64,
+        /// optional, the account which signs, if its key was rotated. Otherwise derived from the key
+        #[clap(short, long)]
+        address: Option<AccountAddress>,
+        /// optional, file to save the signed message, otherwise it is printed
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Verify a signed message, and that its key controls the account on chain
+    VerifyMessage {
+        /// the signed message file from sign-message
+        #[clap(short, long)]
+        file: PathBuf,
+        /// only check the signature, without connecting to a node
+        #[clap(long)]
+        offline: bool,
+        /// optional, URL of the node to check the account, otherwise from the config file
+        #[clap(short, long)]
+        url: Option<Url>,
+        /// optional, path to the libra cli config file
+        #[clap(short, long)]
+        config_path: Option<PathBuf>,
+    },
+    /// Find the mnemonic when one word is mistyped or missing. Use ? for a missing word at a known position
+    Recover {
+        /// the words you have, otherwise they will be prompted
+        #[clap(short, long)]
+        mnemonic: Option<String>,
+        /// prompt for the passphrase of the mnemonic, if it has one
+        #[clap(long)]
+        passphrase: bool,
+        /// only show the candidates which have an account on chain
+        #[clap(long)]
+        check_chain: bool,
+        /// optional, URL of the node to check the accounts, otherwise from the config file
+        #[clap(short, long)]
+        url: Option<Url>,
+        /// optional, path to the libra cli config file
+        #[clap(short, long)]
+        config_path: Option<PathBuf>,
+    },
+    /// Recover the mnemonic from the shares made by `split`
+    Combine {
+        /// the words of a share, repeat it for each share. Otherwise they will be prompted
+        #[clap(short, long)]
+        share: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum KeystoreSub {
+    /// Encrypt the key of a profile with a password, from its mnemonic
+    Import {
+        #[clap(flatten)]
+        opts: KeystoreOpts,
+        /// the mnemonic of the account, otherwise it will be prompted
+        #[clap(short, long)]
+        mnemonic: Option<String>,
+        /// prompt for the mnemonic passphrase, if the account was derived with one
+        #[clap(long)]
+        passphrase: bool,
+    },
+    /// Print the private key of a profile, for a backup
+    Export {
+        #[clap(flatten)]
+        opts: KeystoreOpts,
+    },
+    /// Encrypt the key of a profile with a new password
+    ChangePassword {
+        #[clap(flatten)]
+        opts: KeystoreOpts,
+    },
+}
+
+#[derive(Args, Debug)]
+struct KeystoreOpts {
+    /// optional, path to the libra cli config file
+    #[clap(short, long)]
+    config_path: Option<PathBuf>,
+    /// nickname of the profile, otherwise the default profile
+    #[clap(short, long)]
+    profile: Option<String>,
 }
 
 #[derive(Args, Debug)]
@@ -43,32 +178,246 @@ struct WhoamiOpts {
 
     #[clap(short('m'), long)]
     mnemonic: Option<String>,
+
+    /// prompt for the passphrase of the mnemonic, if it has one
+    #[clap(long)]
+    passphrase: bool,
+
+    /// show the accounts at these child numbers instead of the owner, from 6 upwards. Comma separated
+    #[clap(long, value_delimiter = ',')]
+    index: Vec<u64>,
+
+    /// save the accounts of --index as profiles in the config file
+    #[clap(long, requires = "index")]
+    add_profiles: bool,
+
+    /// optional, path to the libra cli config file
+    #[clap(short, long)]
+    config_path: Option<PathBuf>,
 }
 
 impl WalletCli {
     pub async fn run(&self) -> Result<()> {
         match &self.command {
             WalletSub::Whoami(args) => {
+                let passphrase = if args.passphrase {
+                    load_keys::prompt_passphrase(false)?
+                } else {
+                    String::new()
+                };
+                if !args.index.is_empty() {
+                    let accounts = user_accounts(args.mnemonic.clone(), &passphrase, &args.index)?;
+                    for (i, keys) in &accounts {
+                        println!("account {}: {}", i, keys.account);
+                        println!("authentication key {}: {}", i, keys.auth_key);
+                    }
+                    if args.add_profiles {
+                        let mut app_cfg = AppCfg::load(args.config_path.clone())?;
+                        add_profiles(&mut app_cfg, &accounts)?;
+                        println!("SUCCESS: profiles added to the config file");
+                    }
+                    return Ok(());
+                }
                 who_am_i(
                     args.legacy_address,
                     args.mnemonic.clone(),
                     args.show_validator,
+                    &passphrase,
                 )?;
             }
             WalletSub::Legacy => {
                 println!("this command will generate legacy keys and addresses from v5 addresses. You should only be using this for testing or debugging purposes");
 
-                account_keys::legacy_keygen(true)?;
+                account_keys::legacy_keygen(true, "")?;
+            }
+            WalletSub::Keystore(sub) => sub.run()?,
+            WalletSub::Split {
+                mnemonic,
+                shares,
+                threshold,
+                output_dir,
+            } => {
+                let mnemonic = match mnemonic {
+                    Some(m) => m.to_owned(),
+                    None => load_keys::get_account_from_prompt().2.mnemonic(),
+                };
+                let list = shamir::split(&Mnemonic::from(mnemonic.trim())?, *threshold, *shares)?;
+                match output_dir {
+                    Some(dir) => {
+                        std::fs::create_dir_all(dir)?;
+                        for s in &list {
+                            let path = dir.join(format!("share_{}.txt", s.index));
+                            write_to_user_only_file(
+                                &path,
+                                &path.display().to_string(),
+                                s.to_words().as_bytes(),
+                            )?;
+                        }
+                        println!("SUCCESS: {} shares saved in {}", list.len(), dir.display());
+                    }
+                    None => {
+                        for s in &list {
+                            println!("share {}: {}", s.index, s);
+                        }
+                    }
+                }
+                println!(
+                    "Keep the shares apart, any {} of them recover the mnemonic",
+                    threshold
+                );
+            }
+            WalletSub::SignMessage {
+                message,
+                mnemonic,
+                passphrase,
+                index,
+                address,
+                output,
+            } => {
+                let passphrase = if *passphrase {
+                    load_keys::prompt_passphrase(false)?
+                } else {
+                    String::new()
+                };
+                let mnemonic = match mnemonic {
+                    Some(m) => m.to_owned(),
+                    None => load_keys::get_account_from_prompt().2.mnemonic(),
+                };
+                let wallet = WalletLibrary::new_from_mnemonic_with_passphrase(
+                    Mnemonic::from(mnemonic.trim())?,
+                    &passphrase,
+                );
+                // checks the index is not one of the validator keys
+                account_keys::get_account_at_index(&wallet, *index)?;
+                let key = wallet
+                    .get_key_factory()
+                    .private_child(ChildNumber::new(*index))?;
+                let address = address.unwrap_or_else(|| key.get_address());
+
+                let signed = SignedMessage::sign(&key, address, message)?;
+                match output {
+                    Some(p) => {
+                        signed.save(p)?;
+                        println!("SUCCESS: signed message saved to {}", p.display());
+                    }
+                    None => println!("{}", serde_json::to_string_pretty(&signed)?),
+                }
+            }
+            WalletSub::VerifyMessage {
+                file,
+                offline,
+                url,
+                config_path,
+            } => {
+                let signed = SignedMessage::read(file)?;
+                if *offline {
+                    signed.verify_signature()?;
+                    println!(
+                        "SUCCESS: the signature is valid, the key was not checked against account {}",
+                        signed.address
+                    );
+                    return Ok(());
+                }
+                let client = match url {
+                    Some(u) => Client::new(u.to_owned()),
+                    None => {
+                        let app_cfg = AppCfg::load(config_path.clone())?;
+                        Client::from_libra_config(&app_cfg, None).await?.0
+                    }
+                };
+                signed.verify_on_chain(&client).await?;
+                println!(
+                    "SUCCESS: account {} signed the message: {}",
+                    signed.address, signed.message
+                );
+            }
+            WalletSub::Recover {
+                mnemonic,
+                passphrase,
+                check_chain,
+                url,
+                config_path,
+            } => {
+                let phrase = match mnemonic {
+                    Some(m) => m.to_owned(),
+                    None => {
+                        println!("Enter the words you have, with ? for a missing word:");
+                        rpassword::read_password_from_tty(Some("\u{1F511} "))?
+                    }
+                };
+                let passphrase = if *passphrase {
+                    load_keys::prompt_passphrase(false)?
+                } else {
+                    String::new()
+                };
+                let candidates = recover::recover_candidates(&phrase)?;
+                if candidates.is_empty() {
+                    bail!("no mnemonic with a valid checksum is near these words");
+                }
+                println!("{} candidates with a valid checksum", candidates.len());
+
+                let found = if *check_chain {
+                    let client = match url {
+                        Some(u) => Client::new(u.to_owned()),
+                        None => {
+                            let app_cfg = AppCfg::load(config_path.clone())?;
+                            Client::from_libra_config(&app_cfg, None).await?.0
+                        }
+                    };
+                    recover::find_on_chain(&client, &candidates, &passphrase).await?
+                } else {
+                    candidates
+                        .into_iter()
+                        .map(|m| {
+                            let keys = account_keys::get_keys_from_mnem_with_passphrase(
+                                m.to_string(),
+                                &passphrase,
+                            )?;
+                            Ok((m, keys.child_0_owner.account))
+                        })
+                        .collect::<Result<Vec<_>>>()?
+                };
+                if found.is_empty() {
+                    bail!("none of the candidates has an account on chain");
+                }
+                println!("WARNING: anyone with the mnemonic controls the account");
+                for (m, account) in found {
+                    println!("account {}: {}", account, m);
+                }
+            }
+            WalletSub::Combine { share } => {
+                let words = if share.is_empty() {
+                    prompt_shares()?
+                } else {
+                    share.to_owned()
+                };
+                let shares = words
+                    .iter()
+                    .map(|w| shamir::Share::from_words(w))
+                    .collect::<Result<Vec<_>>>()?;
+                let mnemonic = shamir::combine(&shares)?;
+                let keys = account_keys::get_keys_from_mnem(mnemonic.to_string())?;
+                println!("WARNING: anyone with this mnemonic controls the account");
+                println!("{}", mnemonic);
+                println!("account: {}", keys.child_0_owner.account);
             }
             WalletSub::Keygen {
                 mnemonic,
                 output_dir,
+                passphrase,
             } => {
+                // a new mnemonic gets a new passphrase, so confirm it
+                let passphrase = if *passphrase {
+                    load_keys::prompt_passphrase(mnemonic.is_none())?
+                } else {
+                    String::new()
+                };
                 println!(
                     "{}",
                     crate::key_gen::run(
                         mnemonic.to_owned(),
-                        output_dir.as_ref().map(PathBuf::from)
+                        output_dir.as_ref().map(PathBuf::from),
+                        &passphrase,
                     )
                     .await?
                 );
@@ -77,3 +426,100 @@ impl WalletCli {
         Ok(())
     }
 }
+
+/// Asks for the shares one at a time, until an empty line.
+fn prompt_shares() -> Result<Vec<String>> {
+    let mut list = vec![];
+    loop {
+        println!("Enter share {}, or nothing when done:", list.len() + 1);
+        let s = rpassword::read_password_from_tty(Some("\u{1F511} "))?;
+        if s.trim().is_empty() {
+            break;
+        }
+        list.push(s.trim().to_owned());
+    }
+    Ok(list)
+}
+
+impl KeystoreSub {
+    fn run(&self) -> Result<()> {
+        match self {
+            KeystoreSub::Import {
+                opts,
+                mnemonic,
+                passphrase,
+            } => {
+                let config_file = opts.config_file();
+                let app_cfg = AppCfg::load(Some(config_file.clone()))?;
+                let profile = app_cfg.get_profile(opts.profile.clone())?;
+                let passphrase = Zeroizing::new(if *passphrase {
+                    load_keys::prompt_passphrase(false)?
+                } else {
+                    String::new()
+                });
+                // accounts other than the owner are at another child number of the mnemonic
+                let index = profile.derivation_index.unwrap_or(0);
+                let key = match mnemonic {
+                    Some(m) => {
+                        account_keys::get_key_at_index_from_mnem(m.to_owned(), &passphrase, index)?
+                    }
+                    None => account_keys::get_key_at_index_from_prompt(&passphrase, index)?,
+                };
+                // the account may have rotated its key, so compare auth keys not addresses
+                if AuthenticationKey::ed25519(&key.public_key()) != profile.auth_key {
+                    bail!(
+                        "the mnemonic does not match the auth key of profile {}",
+                        profile.nickname
+                    );
+                }
+                let password = keystore::prompt_new_password()?;
+                let path = keystore::import_key(
+                    keystore::config_dir(&config_file),
+                    &key,
+                    profile.account,
+                    &password,
+                )?;
+                println!("SUCCESS: key saved in {}", path.display());
+            }
+            KeystoreSub::Export { opts } => {
+                let path = opts.keystore_path()?;
+                let password = keystore::prompt_password("Enter the keystore password:")?;
+                let key = keystore::export_key(&path, &password)?;
+                println!("WARNING: anyone with this key controls the account");
+                println!("{}", key);
+            }
+            KeystoreSub::ChangePassword { opts } => {
+                let path = opts.keystore_path()?;
+                let sealed = EncryptedKey::read(&path)?;
+                let old = keystore::prompt_password("Enter the current keystore password:")?;
+                // check the old password before asking for a new one
+                sealed.open(&old)?;
+                let new = keystore::prompt_new_password()?;
+                sealed.change_password(&old, &new)?.save(&path)?;
+                println!("SUCCESS: keystore password changed");
+            }
+        }
+        Ok(())
+    }
+}
+
+impl KeystoreOpts {
+    /// the keystore is next to the config file
+    fn config_file(&self) -> PathBuf {
+        self.config_path.clone().unwrap_or_else(default_file_path)
+    }
+
+    fn keystore_path(&self) -> Result<PathBuf> {
+        let config_file = self.config_file();
+        let app_cfg = AppCfg::load(Some(config_file.clone()))?;
+        let profile = app_cfg.get_profile(self.profile.clone())?;
+        let path = keystore::keystore_path(keystore::config_dir(&config_file), profile.account);
+        if !path.exists() {
+            bail!(
+                "no keystore for profile {}, import it with `libra wallet keystore import`",
+                profile.nickname
+            );
+        }
+        Ok(path)
+    }
+}
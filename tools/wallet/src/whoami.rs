@@ -1,11 +1,18 @@
-use crate::keys::make_validator_keys;
+use crate::{
+    account_keys::{get_account_at_index, AccountKeys},
+    core::{mnemonic::Mnemonic, wallet_library::WalletLibrary},
+    keys::make_validator_keys,
+    load_keys,
+};
 use dialoguer::Confirm;
+use libra_types::core_types::app_cfg::{AppCfg, Profile};
 
 // given a mnemonic what are all the settings which could be expected
 pub fn who_am_i(
     legacy_address_opt: bool,
     mnemonic: Option<String>,
     show_validator: bool,
+    passphrase: &str,
 ) -> anyhow::Result<()> {
     let keep_legacy_address = legacy_address_opt
         || Confirm::new()
@@ -16,7 +23,7 @@ pub fn who_am_i(
     // info
     // the owner key will derive to the same.
     let (_validator_blob, _vfn_blob, _private_identity, public_identity, _legacy_keys) =
-        make_validator_keys(mnemonic, keep_legacy_address)?;
+        make_validator_keys(mnemonic, keep_legacy_address, passphrase)?;
 
     if show_validator {
         println!("validator public credentials:");
@@ -34,3 +41,38 @@ pub fn who_am_i(
 
     Ok(())
 }
+
+/// The accounts at other child numbers of the mnemonic, e.g. 6, 7, ...
+pub fn user_accounts(
+    mnemonic: Option<String>,
+    passphrase: &str,
+    indices: &[u64],
+) -> anyhow::Result<Vec<(u64, AccountKeys)>> {
+    let mnemonic = match mnemonic {
+        Some(m) => m,
+        None => load_keys::get_account_from_prompt().2.mnemonic(),
+    };
+    let wallet = WalletLibrary::new_from_mnemonic_with_passphrase(
+        Mnemonic::from(mnemonic.trim())?,
+        passphrase,
+    );
+    indices
+        .iter()
+        .map(|&i| Ok((i, get_account_at_index(&wallet, i)?)))
+        .collect()
+}
+
+/// Save the accounts as profiles of the config file, so that they can sign with --profile
+pub fn add_profiles(app_cfg: &mut AppCfg, accounts: &[(u64, AccountKeys)]) -> anyhow::Result<()> {
+    for (index, keys) in accounts {
+        let mut profile = Profile::new(keys.auth_key, keys.account);
+        profile.derivation_index = Some(*index);
+        println!(
+            "adding profile {} for account {} at index {}",
+            profile.nickname, keys.account, index
+        );
+        app_cfg.maybe_add_profile(profile)?;
+    }
+    app_cfg.save_file()?;
+    Ok(())
+}
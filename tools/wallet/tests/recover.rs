@@ -0,0 +1,16 @@
+use libra_smoke_tests::libra_smoke::LibraSmoke;
+use libra_wallet::{core::mnemonic::Mnemonic, recover::find_on_chain};
+
+/// a candidate with no account on chain is skipped, not an error
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn find_on_chain_skips_missing_accounts() {
+    let mut s = LibraSmoke::new(None, None)
+        .await
+        .expect("could not start swarm");
+
+    let no_account = Mnemonic::new(&[7u8; 32]).unwrap();
+    let list = find_on_chain(&s.client(), &[no_account], "")
+        .await
+        .expect("lookup of a missing account should not fail");
+    assert!(list.is_empty());
+}
@@ -15,6 +15,7 @@ use url::Url;
 use std::{fs, io::Write, path::PathBuf, str::FromStr};
 
 use super::{
+    cli_error::CliError,
     mode_ol::MODE_0L,
     network_playlist::{self, NetworkPlaylist},
     pledge::Pledge,
@@ -24,9 +25,17 @@ use super::{
 pub const MINIMUM_GAS_PRICE_IN_DIEM: u64 = GAS_UNIT_PRICE;
 
 pub const CONFIG_FILE_NAME: &str = "libra-cli-config.yaml";
+
+/// The schema version of libra-cli-config.yaml. Files without a version are
+/// from before it was versioned, and are upgraded with `libra config migrate`.
+pub const CONFIG_VERSION: u64 = 1;
+
 /// MinerApp Configuration
 #[derive(Debug, Deserialize, Serialize)]
 pub struct AppCfg {
+    /// Schema version of the file, see CONFIG_VERSION
+    #[serde(default)]
+    pub config_version: u64,
     /// Workspace config
     pub workspace: Workspace,
     /// A user may have multiple profiles for different accounts or networks
@@ -37,17 +46,42 @@ pub struct AppCfg {
     pub tx_configs: TxConfigs,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+/// The 0L.toml of v5 and v6, which had a single profile. Only the fields
+/// which `libra config migrate` keeps are read.
+#[derive(Debug, Deserialize)]
 pub struct LegacyToml {
-    /// Workspace config
-    pub workspace: Workspace,
     /// User Profile
     // NOTE: this profile is being deprecated
-    pub profile: Profile,
+    pub profile: LegacyProfile,
     /// Chain Info for all users
-    pub chain_info: ChainInfo,
+    pub chain_info: Option<LegacyChainInfo>,
     /// Transaction configurations
-    pub tx_configs: TxConfigs,
+    pub tx_configs: Option<TxConfigs>,
+}
+
+/// The profile of a 0L.toml
+#[derive(Debug, Deserialize)]
+pub struct LegacyProfile {
+    /// hex, v5 accounts are 16 bytes and have no 0x
+    pub account: String,
+    pub auth_key: AuthenticationKey,
+    pub statement: Option<String>,
+    #[serde(default)]
+    pub upstream_nodes: Vec<Url>,
+}
+
+/// The chain of a 0L.toml
+#[derive(Debug, Deserialize)]
+pub struct LegacyChainInfo {
+    pub chain_id: Option<LegacyChainId>,
+}
+
+/// The chain id was saved by name or by number
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum LegacyChainId {
+    Name(String),
+    Id(u8),
 }
 
 impl LegacyToml {
@@ -121,16 +155,26 @@ impl AppCfg {
     pub fn load(file: Option<PathBuf>) -> anyhow::Result<Self> {
         let path = file.unwrap_or_else(default_file_path);
         if !path.exists() {
-            bail!(format!(
+            return Err(CliError::config(format!(
                 "libra-cli-config.yaml dir does not exist at {}\nHave you initialized the configs with `libra config init`?",
                 path.to_str().unwrap()
             ))
+            .into());
+        }
+        let s = fs::read_to_string(&path).map_err(CliError::config)?;
+        let de: AppCfg = serde_yaml::from_str(&s).map_err(|e| {
+            CliError::config(format!(
+                "could not read {:?} file into an AppCfg. Is there an issue with the file? Older files can be upgraded with `libra config migrate`. {}",
+                &path, e
+            ))
+        })?;
+        if de.config_version > CONFIG_VERSION {
+            return Err(CliError::config(format!(
+                "{:?} has config version {}, but this version of the tools reads up to {}. Please upgrade the tools",
+                &path, de.config_version, CONFIG_VERSION
+            ))
+            .into());
         }
-        let s = fs::read_to_string(&path)?;
-        let de: AppCfg = serde_yaml::from_str(&s).context(format!(
-            "could not read {:?} file into an AppCfg. Is there an issue with the file?",
-            &path
-        ))?;
         Ok(de)
     }
     /// save the config file to 0L.toml to the workspace home path
@@ -162,12 +206,13 @@ impl AppCfg {
         Ok(home)
     }
 
-    /// get profile index by account fragment: full account string or shortened "nickname"
+    /// get profile index by the exact nickname or the full account address
     fn get_profile_idx(&self, mut nickname: Option<String>) -> anyhow::Result<usize> {
         if self.user_profiles.is_empty() {
             bail!("no profiles found")
         };
-        if self.user_profiles.len() == 1 {
+        // an explicit nickname must match, even if there is only one profile
+        if self.user_profiles.len() == 1 && nickname.is_none() {
             return Ok(0);
         };
 
@@ -177,22 +222,43 @@ impl AppCfg {
         };
 
         if let Some(n) = nickname {
-            let found = self.user_profiles.iter().enumerate().find_map(|(i, e)| {
-                if e.nickname.contains(&n) || e.account.to_string().contains(&n) {
-                    Some(i)
-                } else {
-                    None
-                }
-            });
-            return found.context("could not find a profile");
+            // addresses may be given with or without the 0x
+            let addr =
+                AccountAddress::from_hex_literal(&format!("0x{}", n.trim_start_matches("0x"))).ok();
+            let found: Vec<usize> = self
+                .user_profiles
+                .iter()
+                .enumerate()
+                .filter(|(_, e)| e.nickname == n || Some(e.account) == addr)
+                .map(|(i, _)| i)
+                .collect();
+            match found.as_slice() {
+                [i] => return Ok(*i),
+                [] => bail!("could not find a profile"),
+                _ => bail!(
+                    "{} matches {} profiles, use the full account address",
+                    n,
+                    found.len()
+                ),
+            }
         };
 
         bail!("no profiles found")
     }
 
-    /// can get profile by account fragment: full account string or shortened "nickname"
+    /// a requested profile must exist, otherwise fall back to the first profile
+    fn profile_idx_or_first(&self, nickname: Option<String>) -> anyhow::Result<usize> {
+        match nickname {
+            Some(n) => self
+                .get_profile_idx(Some(n.clone()))
+                .context(format!("no profile matching {}", n)),
+            None => Ok(self.get_profile_idx(None).unwrap_or(0)),
+        }
+    }
+
+    /// can get profile by the full account string or the "nickname"
     pub fn get_profile(&self, nickname: Option<String>) -> anyhow::Result<&Profile> {
-        let idx = self.get_profile_idx(nickname).unwrap_or(0);
+        let idx = self.profile_idx_or_first(nickname)?;
         let p = self.user_profiles.get(idx).context("no profile at index")?;
         // The privilege to use this software depends on the user upholding a code of conduct and taking the pledge. Totally cool if you don't want to, but you'll need to write your own tools.
         if !p.check_has_pledge(0) {
@@ -203,7 +269,7 @@ impl AppCfg {
 
     /// get profile mutable borrow
     pub fn get_profile_mut(&mut self, nickname: Option<String>) -> anyhow::Result<&mut Profile> {
-        let idx = self.get_profile_idx(nickname).unwrap_or(0);
+        let idx = self.profile_idx_or_first(nickname)?;
         let p = self
             .user_profiles
             .get_mut(idx)
@@ -234,10 +300,28 @@ impl AppCfg {
     /// remove a profile
     pub fn try_remove_profile(&mut self, nickname: &str) -> anyhow::Result<()> {
         let idx = self.get_profile_idx(Some(nickname.to_owned()))?;
-        self.user_profiles.remove(idx);
+        let removed = self.user_profiles.remove(idx);
+        // don't leave a default which points to nothing, or to another profile
+        if self.workspace.default_profile.as_deref() == Some(removed.nickname.as_str()) {
+            self.workspace.default_profile = None;
+        }
+        Ok(())
+    }
+
+    /// set the default profile, which must exist
+    pub fn set_default_profile(&mut self, nickname: &str) -> anyhow::Result<()> {
+        let idx = self.get_profile_idx(Some(nickname.to_owned()))?;
+        let n = self.user_profiles[idx].nickname.clone();
+        self.workspace.set_default(n);
         Ok(())
     }
 
+    /// the network of a profile, otherwise the default chain of the workspace
+    pub fn get_chain_id(&self, nickname: Option<String>) -> anyhow::Result<NamedChain> {
+        let chain = self.get_profile(nickname)?.chain_id;
+        Ok(chain.unwrap_or(self.workspace.default_chain_id))
+    }
+
     pub fn set_chain_id(&mut self, chain_id: NamedChain) {
         self.workspace.default_chain_id = chain_id;
     }
@@ -291,8 +375,9 @@ impl AppCfg {
         &mut self,
         chain_id: Option<NamedChain>,
     ) -> anyhow::Result<NetworkPlaylist> {
-        let mut np = self.get_network_profile(chain_id)?;
+        let np = self.get_network_profile_mut(chain_id)?;
         np.refresh_sync_status().await?;
+        let np = np.to_owned();
         self.save_file()?;
         Ok(np)
     }
@@ -322,6 +407,7 @@ impl AppCfg {
 impl Default for AppCfg {
     fn default() -> Self {
         Self {
+            config_version: CONFIG_VERSION,
             workspace: Workspace::default(),
             user_profiles: vec![],
             network_playlist: vec![],
@@ -381,6 +467,7 @@ impl Default for ChainInfo {
 }
 
 /// Miner profile to commit this work chain to a particular identity
+#[serde_as]
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Profile {
     /// The 0L account for the Miner and prospective validator. This is derived from auth_key
@@ -403,6 +490,13 @@ pub struct Profile {
     pub statement: String,
     /// Pledges the user took
     pub pledges: Option<Vec<Pledge>>,
+    /// The child number of the key in the mnemonic, if it is not the owner key 0
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derivation_index: Option<u64>,
+    /// The network of this profile, otherwise the default chain of the workspace
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<NamedChain>,
 }
 
 impl Default for Profile {
@@ -420,6 +514,8 @@ impl Default for Profile {
             on_chain: false,
             balance: SlowWalletBalance::default(),
             pledges: None,
+            derivation_index: None,
+            chain_id: None,
         }
     }
 }
@@ -543,6 +639,9 @@ pub struct TxConfigs {
     /// Cheap or test transaction costs
     // #[serde(default = "TxCost::default_cheap_txs_cost")]
     pub cheap_txs_cost: Option<TxCost>,
+    /// optional, estimate the gas of each transaction instead of using the costs above
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_gas: Option<AutoGas>,
 }
 
 impl TxConfigs {
@@ -630,6 +729,52 @@ impl Default for TxConfigs {
             management_txs_cost: Some(TxCost::default_management_txs_cost()),
             miner_txs_cost: Some(TxCost::default_miner_txs_cost()),
             cheap_txs_cost: Some(TxCost::default_cheap_txs_cost()),
+            auto_gas: None,
+        }
+    }
+}
+
+/// Automatic gas: the transaction is simulated for the gas it uses, and the
+/// price is the estimate of the node.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AutoGas {
+    /// percent added to the simulated gas, for the max gas of the transaction
+    #[serde(default = "AutoGas::default_margin_pct")]
+    pub max_gas_margin_pct: u64,
+    /// if the transaction expires in the mempool, send it once more at a higher price
+    #[serde(default = "AutoGas::default_retry_expired")]
+    pub retry_expired: bool,
+}
+
+impl AutoGas {
+    fn default_margin_pct() -> u64 {
+        50
+    }
+    fn default_retry_expired() -> bool {
+        true
+    }
+
+    /// the max gas for a transaction which used this much gas in simulation
+    pub fn max_gas(&self, gas_used: u64) -> u64 {
+        let with_margin = gas_used.saturating_mul(100 + self.max_gas_margin_pct) / 100;
+        with_margin.clamp(1, MAX_GAS_AMOUNT)
+    }
+
+    /// the price to retry an expired transaction: the prioritized estimate of
+    /// the node, and at least double the price which expired
+    pub fn retry_price(expired_price: u64, prioritized: Option<u64>) -> u64 {
+        expired_price
+            .saturating_mul(2)
+            .max(prioritized.unwrap_or(0))
+            .max(MINIMUM_GAS_PRICE_IN_DIEM)
+    }
+}
+
+impl Default for AutoGas {
+    fn default() -> Self {
+        Self {
+            max_gas_margin_pct: Self::default_margin_pct(),
+            retry_expired: Self::default_retry_expired(),
         }
     }
 }
@@ -773,3 +918,102 @@ tx_configs:
     let url = cfg.pick_url(None).unwrap();
     assert!(url.host_str().unwrap().contains("localhost"));
 }
+
+#[test]
+fn switch_profiles() {
+    let a = AuthenticationKey::from_str(
+        "8603ba96e87b810cebbec1a0fd7ea06285f9eb352a3eabde992a5594fe80af40",
+    )
+    .unwrap();
+    let b = AuthenticationKey::from_str(
+        "052dea65ac80cd4b2b1318a9420dc1568819882769346d9acffdc0d731504c66",
+    )
+    .unwrap();
+
+    let mut cfg = AppCfg {
+        user_profiles: vec![Profile::new(a, a.derived_address())],
+        ..Default::default()
+    };
+    // with one profile, a nickname which does not match is an error
+    assert!(cfg.get_profile(Some("operator".to_owned())).is_err());
+    assert!(cfg.get_profile(None).is_ok());
+
+    let mut operator = Profile::new(b, b.derived_address());
+    operator.nickname = "operator".to_owned();
+    operator.chain_id = Some(NamedChain::TESTNET);
+    cfg.maybe_add_profile(operator).unwrap();
+
+    cfg.set_default_profile("operator").unwrap();
+    assert_eq!(cfg.get_profile(None).unwrap().auth_key, b);
+    assert_eq!(cfg.get_chain_id(None).unwrap(), NamedChain::TESTNET);
+    assert_eq!(
+        cfg.get_chain_id(Some(a.derived_address().to_hex_literal()))
+            .unwrap(),
+        cfg.workspace.default_chain_id
+    );
+    assert!(cfg.set_default_profile("nobody").is_err());
+
+    // the chain is saved with the profile
+    let yaml = serde_yaml::to_string(&cfg).unwrap();
+    let read: AppCfg = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(read.get_chain_id(None).unwrap(), NamedChain::TESTNET);
+
+    // fragments of a nickname or an address do not match
+    assert!(cfg.get_profile(Some("oper".to_owned())).is_err());
+    assert!(cfg
+        .get_profile(Some(a.derived_address().to_string()[..8].to_owned()))
+        .is_err());
+    assert_eq!(
+        cfg.get_profile(Some(b.derived_address().to_string()))
+            .unwrap()
+            .nickname,
+        "operator"
+    );
+
+    // a nickname used by two profiles is ambiguous
+    let c = AuthenticationKey::from_str(
+        "4e5a1ba05c5e7f0ee2c2a0ec7c9e5fd5aa2e7b2b7e02b0a1b1e1d1c1b1a19181",
+    )
+    .unwrap();
+    let mut other = Profile::new(c, c.derived_address());
+    other.nickname = "operator".to_owned();
+    cfg.maybe_add_profile(other).unwrap();
+    assert!(cfg.get_profile(Some("operator".to_owned())).is_err());
+    cfg.try_remove_profile(&c.derived_address().to_hex_literal())
+        .unwrap();
+
+    cfg.try_remove_profile("operator").unwrap();
+    assert!(cfg.workspace.default_profile.is_none());
+    assert_eq!(cfg.get_profile(None).unwrap().auth_key, a);
+}
+
+#[test]
+fn auto_gas_margin() {
+    let a = AutoGas::default();
+    assert_eq!(a.max_gas(1000), 1500);
+    assert_eq!(a.max_gas(0), 1);
+    assert_eq!(a.max_gas(u64::MAX), MAX_GAS_AMOUNT);
+    assert_eq!(AutoGas::retry_price(100, Some(150)), 200);
+    assert_eq!(AutoGas::retry_price(100, Some(500)), 500);
+
+    // older files have no auto_gas, and a partial one gets the defaults
+    let older = r"
+baseline_cost:
+  max_gas_unit_for_tx: 1
+  coin_price_per_unit: 1
+  user_tx_timeout: 1
+critical_txs_cost: null
+management_txs_cost: null
+miner_txs_cost: null
+cheap_txs_cost: null
+";
+    let t: TxConfigs = serde_yaml::from_str(older).unwrap();
+    assert!(t.auto_gas.is_none());
+    let partial = format!("{}auto_gas:\n  max_gas_margin_pct: 20\n", older);
+    let auto = serde_yaml::from_str::<TxConfigs>(&partial)
+        .unwrap()
+        .auto_gas
+        .unwrap();
+    assert_eq!(auto.max_gas(1000), 1200);
+    assert!(auto.retry_expired);
+}
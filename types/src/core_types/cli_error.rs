@@ -0,0 +1,251 @@
+//! Categorized errors for the cli tools, so scripts can tell failures apart
+//! by the process exit code.
+
+use crate::core_types::error_map::{error_category, explain_abort, ErrorMaps};
+use diem_sdk::rest_client::error::RestError;
+use diem_types::{transaction::ExecutionStatus, vm_status::AbortLocation};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// What kind of failure ended the command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    /// anything which is not categorized below
+    Unknown,
+    /// invalid arguments or input files
+    BadInput,
+    /// the libra-cli-config.yaml is missing or cannot be read
+    Config,
+    /// the upstream node could not be reached, or its API returned an error
+    Network,
+    /// the transaction was executed, and it failed in the VM
+    VmAbort,
+}
+
+impl ErrorCategory {
+    /// The exit code of the process. These are stable, automation depends on them.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ErrorCategory::Unknown => 1,
+            ErrorCategory::BadInput => 2,
+            ErrorCategory::Config => 3,
+            ErrorCategory::Network => 4,
+            ErrorCategory::VmAbort => 5,
+        }
+    }
+}
+
+/// Where and why a transaction failed in the VM.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveAbort {
+    /// the module which aborted e.g. 0x1::ol_account, or "script"
+    pub location: Option<String>,
+    /// the abort code, or the failing instruction for an execution failure
+    pub code: Option<u64>,
+    /// the std::error category of the abort code, e.g. INVALID_ARGUMENT
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// the name of the error constant, from the VM or the framework error maps
+    pub reason_name: Option<String>,
+    /// the doc comment of the error constant
+    pub description: Option<String>,
+    /// the full VM status
+    pub vm_status: String,
+}
+
+impl MoveAbort {
+    /// The abort details of a status. With the framework error maps the
+    /// error constant is found even if the chain did not have the error map.
+    pub fn from_status(status: &ExecutionStatus, maps: Option<&ErrorMaps>) -> Self {
+        let location_str = |l: &AbortLocation| match l {
+            AbortLocation::Module(m) => m.short_str_lossless(),
+            AbortLocation::Script => "script".to_owned(),
+        };
+
+        let mut abort = MoveAbort {
+            location: None,
+            code: None,
+            category: None,
+            reason_name: None,
+            description: None,
+            vm_status: format!("{:?}", status),
+        };
+
+        match status {
+            ExecutionStatus::MoveAbort {
+                location,
+                code,
+                info,
+            } => {
+                abort.location = Some(location_str(location));
+                abort.code = Some(*code);
+                if let Some(i) = info {
+                    abort.reason_name = Some(i.reason_name.to_owned());
+                    abort.description = Some(i.description.to_owned());
+                }
+                abort.category = error_category(*code).map(str::to_owned);
+                if let Some(e) = maps.and_then(|m| explain_abort(m, &location_str(location), *code))
+                {
+                    abort.reason_name.get_or_insert(e.reason_name);
+                    abort.description.get_or_insert(e.description);
+                }
+            }
+            ExecutionStatus::ExecutionFailure {
+                location,
+                code_offset,
+                ..
+            } => {
+                abort.location = Some(location_str(location));
+                abort.code = Some(*code_offset as u64);
+            }
+            _ => {}
+        }
+        abort
+    }
+
+    /// One line for people, e.g. 0x1::slow_wallet::EGENESIS_ERROR (INVALID_ARGUMENT): ...
+    pub fn explain(&self) -> Option<String> {
+        let name = self.reason_name.as_ref()?;
+        let mut s = format!("{}::{}", self.location.as_deref().unwrap_or("?"), name);
+        if let Some(c) = &self.category {
+            s.push_str(&format!(" ({})", c));
+        }
+        if let Some(d) = &self.description {
+            s.push_str(&format!(": {}", d));
+        }
+        Some(s)
+    }
+}
+
+/// An error with a category, and the abort details if a transaction failed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CliError {
+    pub category: ErrorCategory,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub move_abort: Option<MoveAbort>,
+}
+
+impl CliError {
+    pub fn new(category: ErrorCategory, message: impl ToString) -> Self {
+        Self {
+            category,
+            message: message.to_string(),
+            move_abort: None,
+        }
+    }
+
+    pub fn config(message: impl ToString) -> Self {
+        Self::new(ErrorCategory::Config, message)
+    }
+
+    pub fn bad_input(message: impl ToString) -> Self {
+        Self::new(ErrorCategory::BadInput, message)
+    }
+
+    /// a transaction which was committed but did not succeed
+    pub fn vm_abort(status: &ExecutionStatus, maps: Option<&ErrorMaps>) -> Self {
+        let abort = MoveAbort::from_status(status, maps);
+        let message = match abort.explain() {
+            Some(why) => format!("transaction failed, {}, status: {:?}", why, status),
+            None => format!("transaction failed, status: {:?}", status),
+        };
+        Self {
+            category: ErrorCategory::VmAbort,
+            message,
+            move_abort: Some(abort),
+        }
+    }
+
+    /// Categorize any error returned by the tools. Errors tagged with a
+    /// CliError anywhere in the chain keep their category, otherwise
+    /// we look for errors from the REST client.
+    pub fn from_anyhow(e: &anyhow::Error) -> Self {
+        // the full chain of context is more useful than the inner message
+        let message = format!("{:#}", e);
+
+        if let Some(c) = e.chain().find_map(|c| c.downcast_ref::<CliError>()) {
+            return Self {
+                message,
+                ..c.to_owned()
+            };
+        }
+
+        let is_network = e
+            .chain()
+            .any(|c| c.is::<RestError>() || c.is::<reqwest::Error>());
+        if is_network {
+            return Self::new(ErrorCategory::Network, message);
+        }
+
+        if e.chain().any(|c| c.is::<serde_yaml::Error>()) {
+            return Self::new(ErrorCategory::Config, message);
+        }
+
+        Self::new(ErrorCategory::Unknown, message)
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for CliError {}
+
+#[test]
+fn categorize_errors() {
+    use anyhow::Context;
+    use move_core_types::{
+        errmap::ErrorDescription,
+        identifier::Identifier,
+        language_storage::{ModuleId, CORE_CODE_ADDRESS},
+    };
+
+    let e = anyhow::Error::new(CliError::config("no config file")).context("could not load");
+    let c = CliError::from_anyhow(&e);
+    assert_eq!(c.category, ErrorCategory::Config);
+    assert_eq!(c.category.exit_code(), 3);
+    assert!(c.message.contains("could not load"));
+    assert!(c.message.contains("no config file"));
+
+    let e = anyhow::anyhow!("something else");
+    assert_eq!(CliError::from_anyhow(&e).category, ErrorCategory::Unknown);
+
+    let c = CliError::vm_abort(&ExecutionStatus::OutOfGas, None);
+    assert_eq!(c.category.exit_code(), 5);
+    assert!(c.move_abort.unwrap().code.is_none());
+
+    // the framework error maps explain an abort the chain did not
+    let mut maps = ErrorMaps::new();
+    maps.entry("0x1::slow_wallet".to_owned())
+        .or_default()
+        .insert(
+            1,
+            ErrorDescription {
+                code_name: "EGENESIS_ERROR".to_owned(),
+                code_description: "genesis error".to_owned(),
+            },
+        );
+    let status = ExecutionStatus::MoveAbort {
+        location: AbortLocation::Module(ModuleId::new(
+            CORE_CODE_ADDRESS,
+            Identifier::new("slow_wallet").unwrap(),
+        )),
+        code: 0x10001,
+        info: None,
+    };
+    let c = CliError::vm_abort(&status, Some(&maps));
+    assert!(c.message.contains("EGENESIS_ERROR"));
+    assert_eq!(
+        c.move_abort.unwrap().category.as_deref(),
+        Some("INVALID_ARGUMENT")
+    );
+
+    // without the maps there is still the category
+    let abort = CliError::vm_abort(&status, None).move_abort.unwrap();
+    assert!(abort.reason_name.is_none());
+    assert_eq!(abort.category.as_deref(), Some("INVALID_ARGUMENT"));
+}
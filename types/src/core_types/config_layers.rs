@@ -0,0 +1,456 @@
+//! Layered config for CI and containers. Each value comes from the first of:
+//! the CLI flags, the LIBRA_* environment variables, libra-cli-config.yaml,
+//! or the built-in default.
+//!
+//! The MODE_0L variable is not a layer, it still selects the test behaviors
+//! of the tools. Use LIBRA_CHAIN_ID to pick the chain.
+
+use super::{
+    app_cfg::{default_file_path, AppCfg, AutoGas, TxCost},
+    network_playlist::{HostProfile, NetworkPlaylist},
+};
+use crate::exports::NamedChain;
+use anyhow::Context;
+use std::{collections::BTreeMap, env, fmt, path::PathBuf, str::FromStr};
+use url::Url;
+
+/// path of the config file
+pub const ENV_CONFIG_PATH: &str = "LIBRA_CONFIG_PATH";
+/// workspace.node_home
+pub const ENV_NODE_HOME: &str = "LIBRA_NODE_HOME";
+/// the chain, e.g. TESTNET
+pub const ENV_CHAIN_ID: &str = "LIBRA_CHAIN_ID";
+/// nickname or address of the profile
+pub const ENV_PROFILE: &str = "LIBRA_PROFILE";
+/// upstream nodes, comma separated. These replace the playlist of the chain
+pub const ENV_UPSTREAM_URLS: &str = "LIBRA_UPSTREAM_URLS";
+/// max gas units, for every transaction type
+pub const ENV_MAX_GAS_UNIT_FOR_TX: &str = "LIBRA_MAX_GAS_UNIT_FOR_TX";
+/// gas price, for every transaction type
+pub const ENV_COIN_PRICE_PER_UNIT: &str = "LIBRA_COIN_PRICE_PER_UNIT";
+/// transaction timeout, for every transaction type
+pub const ENV_USER_TX_TIMEOUT: &str = "LIBRA_USER_TX_TIMEOUT";
+/// true to estimate the gas of each transaction, see AutoGas
+pub const ENV_AUTO_GAS: &str = "LIBRA_AUTO_GAS";
+
+const KEY_CONFIG_PATH: &str = "config_path";
+const KEY_NODE_HOME: &str = "workspace.node_home";
+const KEY_CHAIN_ID: &str = "workspace.default_chain_id";
+const KEY_PROFILE: &str = "workspace.default_profile";
+const KEY_UPSTREAM_URLS: &str = "network_playlist.nodes";
+const KEY_MAX_GAS: &str = "tx_configs.max_gas_unit_for_tx";
+const KEY_GAS_PRICE: &str = "tx_configs.coin_price_per_unit";
+const KEY_TX_TIMEOUT: &str = "tx_configs.user_tx_timeout";
+const KEY_AUTO_GAS: &str = "tx_configs.auto_gas";
+
+/// Where an effective value came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueSource {
+    Default,
+    File(PathBuf),
+    Env(&'static str),
+    Cli,
+}
+
+impl fmt::Display for ValueSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueSource::Default => write!(f, "default"),
+            ValueSource::File(p) => write!(f, "file {}", p.display()),
+            ValueSource::Env(v) => write!(f, "env {}", v),
+            ValueSource::Cli => write!(f, "cli flag"),
+        }
+    }
+}
+
+/// The values which can be set by environment variables or CLI flags. Unset
+/// values are taken from the layer below.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOverrides {
+    pub config_path: Option<PathBuf>,
+    pub node_home: Option<PathBuf>,
+    pub chain_id: Option<NamedChain>,
+    pub profile: Option<String>,
+    pub upstream_urls: Vec<Url>,
+    pub max_gas_unit_for_tx: Option<u64>,
+    pub coin_price_per_unit: Option<u64>,
+    pub user_tx_timeout: Option<u64>,
+    pub auto_gas: Option<bool>,
+}
+
+impl ConfigOverrides {
+    /// Read the LIBRA_* environment variables
+    pub fn from_env() -> anyhow::Result<Self> {
+        Self::from_vars(|k| env::var(k).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> anyhow::Result<Self> {
+        fn parse<T: FromStr>(
+            var: &impl Fn(&str) -> Option<String>,
+            name: &str,
+        ) -> anyhow::Result<Option<T>>
+        where
+            T::Err: fmt::Display,
+        {
+            match var(name).filter(|v| !v.trim().is_empty()) {
+                Some(v) => v
+                    .trim()
+                    .parse()
+                    .map(Some)
+                    .map_err(|e| anyhow::anyhow!("invalid value for {}: {}, {}", name, v, e)),
+                None => Ok(None),
+            }
+        }
+
+        let upstream_urls = match var(ENV_UPSTREAM_URLS) {
+            Some(list) => list
+                .split(',')
+                .map(str::trim)
+                .filter(|u| !u.is_empty())
+                .map(|u| {
+                    u.parse()
+                        .context(format!("invalid url in {}: {}", ENV_UPSTREAM_URLS, u))
+                })
+                .collect::<anyhow::Result<Vec<Url>>>()?,
+            None => vec![],
+        };
+
+        Ok(Self {
+            config_path: parse(&var, ENV_CONFIG_PATH)?,
+            node_home: parse(&var, ENV_NODE_HOME)?,
+            chain_id: parse::<String>(&var, ENV_CHAIN_ID)?
+                .map(|c| NamedChain::from_str(&c.to_uppercase()))
+                .transpose()
+                .map_err(|e| anyhow::anyhow!("invalid value for {}: {}", ENV_CHAIN_ID, e))?,
+            profile: parse(&var, ENV_PROFILE)?,
+            upstream_urls,
+            max_gas_unit_for_tx: parse(&var, ENV_MAX_GAS_UNIT_FOR_TX)?,
+            coin_price_per_unit: parse(&var, ENV_COIN_PRICE_PER_UNIT)?,
+            user_tx_timeout: parse(&var, ENV_USER_TX_TIMEOUT)?,
+            auto_gas: parse(&var, ENV_AUTO_GAS)?,
+        })
+    }
+
+    /// Set the cost overrides from a --tx-cost
+    pub fn with_tx_cost(mut self, cost: Option<&TxCost>) -> Self {
+        if let Some(c) = cost {
+            self.max_gas_unit_for_tx = Some(c.max_gas_unit_for_tx);
+            self.coin_price_per_unit = Some(c.coin_price_per_unit);
+            self.user_tx_timeout = Some(c.user_tx_timeout);
+        }
+        self
+    }
+}
+
+/// The effective config after all the layers, and where each value came from.
+#[derive(Debug)]
+pub struct ResolvedCfg {
+    pub cfg: AppCfg,
+    /// the chain to use: an override, the chain of the profile, or the default chain
+    pub chain_id: NamedChain,
+    /// the config file which was read, if any
+    pub config_path: Option<PathBuf>,
+    sources: BTreeMap<&'static str, ValueSource>,
+}
+
+impl ResolvedCfg {
+    /// Load the config file, and apply the environment variables, then the CLI flags.
+    /// Without a config file, the defaults are used if the upstream nodes are
+    /// set, since there is no other way to reach the chain.
+    pub fn load(cli: &ConfigOverrides) -> anyhow::Result<Self> {
+        Self::load_layers(&ConfigOverrides::from_env()?, cli)
+    }
+
+    /// Like load, but without a config file there is no config, e.g. on an
+    /// offline signing machine. Invalid LIBRA_* values are still an error.
+    pub fn load_if_exists(cli: &ConfigOverrides) -> anyhow::Result<Option<Self>> {
+        let env = ConfigOverrides::from_env()?;
+        let path = cli
+            .config_path
+            .clone()
+            .or_else(|| env.config_path.clone())
+            .unwrap_or_else(default_file_path);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Self::load_layers(&env, cli).map(Some)
+    }
+
+    fn load_layers(env: &ConfigOverrides, cli: &ConfigOverrides) -> anyhow::Result<Self> {
+        let mut sources = BTreeMap::new();
+
+        let (path, path_source) = match (&cli.config_path, &env.config_path) {
+            (Some(p), _) => (p.clone(), ValueSource::Cli),
+            (None, Some(p)) => (p.clone(), ValueSource::Env(ENV_CONFIG_PATH)),
+            (None, None) => (default_file_path(), ValueSource::Default),
+        };
+        sources.insert(KEY_CONFIG_PATH, path_source);
+
+        let has_urls = !cli.upstream_urls.is_empty() || !env.upstream_urls.is_empty();
+        let config_path = (path.exists() || !has_urls).then_some(path);
+        let mut cfg = if let Some(path) = &config_path {
+            let cfg = AppCfg::load(Some(path.clone()))?;
+            let file = ValueSource::File(path.clone());
+            for k in [
+                KEY_NODE_HOME,
+                KEY_CHAIN_ID,
+                KEY_UPSTREAM_URLS,
+                KEY_MAX_GAS,
+                KEY_GAS_PRICE,
+                KEY_TX_TIMEOUT,
+                KEY_AUTO_GAS,
+            ] {
+                sources.insert(k, file.clone());
+            }
+            if cfg.workspace.default_profile.is_some() {
+                sources.insert(KEY_PROFILE, file);
+            }
+            cfg
+        } else {
+            AppCfg::default()
+        };
+
+        let mut resolved_chain = None;
+        for (layer, source) in [(env, None), (cli, Some(ValueSource::Cli))] {
+            let src = |var| source.clone().unwrap_or(ValueSource::Env(var));
+
+            if let Some(h) = &layer.node_home {
+                cfg.workspace.node_home.clone_from(h);
+                sources.insert(KEY_NODE_HOME, src(ENV_NODE_HOME));
+            }
+            if let Some(p) = &layer.profile {
+                cfg.set_default_profile(p)
+                    .context(format!("cannot select the profile {}", p))?;
+                sources.insert(KEY_PROFILE, src(ENV_PROFILE));
+            }
+            if let Some(c) = layer.chain_id {
+                resolved_chain = Some(c);
+                sources.insert(KEY_CHAIN_ID, src(ENV_CHAIN_ID));
+            }
+            let cost = (
+                layer.max_gas_unit_for_tx,
+                layer.coin_price_per_unit,
+                layer.user_tx_timeout,
+            );
+            if cost.0.is_some() {
+                sources.insert(KEY_MAX_GAS, src(ENV_MAX_GAS_UNIT_FOR_TX));
+            }
+            if cost.1.is_some() {
+                sources.insert(KEY_GAS_PRICE, src(ENV_COIN_PRICE_PER_UNIT));
+            }
+            if cost.2.is_some() {
+                sources.insert(KEY_TX_TIMEOUT, src(ENV_USER_TX_TIMEOUT));
+            }
+            let tx = &mut cfg.tx_configs;
+            if let Some(auto) = layer.auto_gas {
+                // keep the settings of the file, if it has some
+                tx.auto_gas = match (auto, tx.auto_gas.take()) {
+                    (true, a) => Some(a.unwrap_or_default()),
+                    (false, _) => None,
+                };
+                sources.insert(KEY_AUTO_GAS, src(ENV_AUTO_GAS));
+            }
+            for c in [
+                Some(&mut tx.baseline_cost),
+                tx.critical_txs_cost.as_mut(),
+                tx.management_txs_cost.as_mut(),
+                tx.miner_txs_cost.as_mut(),
+                tx.cheap_txs_cost.as_mut(),
+            ]
+            .into_iter()
+            .flatten()
+            {
+                override_cost(c, cost);
+            }
+        }
+
+        // a profile may be on another chain than the default one
+        let chain_id = match resolved_chain {
+            Some(c) => c,
+            None => cfg
+                .get_chain_id(None)
+                .unwrap_or(cfg.workspace.default_chain_id),
+        };
+        // so that the code which reads the default chain uses the same one
+        cfg.set_chain_id(chain_id);
+
+        // the upstream nodes replace the playlist of the chain which is used
+        for (urls, source) in [
+            (&env.upstream_urls, ValueSource::Env(ENV_UPSTREAM_URLS)),
+            (&cli.upstream_urls, ValueSource::Cli),
+        ] {
+            if urls.is_empty() {
+                continue;
+            }
+            let nodes = urls.iter().cloned().map(HostProfile::new).collect();
+            match cfg
+                .network_playlist
+                .iter_mut()
+                .find(|np| np.chain_name == chain_id)
+            {
+                Some(np) => np.nodes = nodes,
+                None => cfg.network_playlist.push(NetworkPlaylist {
+                    chain_name: chain_id,
+                    nodes,
+                }),
+            }
+            sources.insert(KEY_UPSTREAM_URLS, source);
+        }
+
+        Ok(Self {
+            cfg,
+            chain_id,
+            config_path,
+            sources,
+        })
+    }
+
+    /// Where a value came from, by its key
+    pub fn source(&self, key: &str) -> ValueSource {
+        self.sources
+            .get(key)
+            .cloned()
+            .unwrap_or(ValueSource::Default)
+    }
+
+    /// The effective values, with their source, for `config view --resolved`
+    pub fn report(&self) -> Vec<(&'static str, String, ValueSource)> {
+        let cfg = &self.cfg;
+        let cost = cfg.tx_configs.get_cost(None);
+        let nodes = cfg
+            .get_network_profile(Some(self.chain_id))
+            .map(|np| {
+                np.nodes
+                    .iter()
+                    .map(|n| n.url.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .unwrap_or_else(|_| "none".to_owned());
+        let profile = cfg
+            .get_profile(None)
+            .map(|p| format!("{} ({})", p.nickname, p.account))
+            .unwrap_or_else(|_| "none".to_owned());
+        let auto_gas = match &cfg.tx_configs.auto_gas {
+            Some(AutoGas {
+                max_gas_margin_pct,
+                retry_expired,
+            }) => format!(
+                "on, max gas margin {}%, retry expired: {}",
+                max_gas_margin_pct, retry_expired
+            ),
+            None => "off".to_owned(),
+        };
+        let config_path = self
+            .config_path
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| "none".to_owned());
+
+        [
+            (KEY_CONFIG_PATH, config_path),
+            (KEY_NODE_HOME, cfg.workspace.node_home.display().to_string()),
+            (KEY_CHAIN_ID, self.chain_id.to_string()),
+            (KEY_PROFILE, profile),
+            (KEY_UPSTREAM_URLS, nodes),
+            (KEY_MAX_GAS, cost.max_gas_unit_for_tx.to_string()),
+            (KEY_GAS_PRICE, cost.coin_price_per_unit.to_string()),
+            (KEY_TX_TIMEOUT, cost.user_tx_timeout.to_string()),
+            (KEY_AUTO_GAS, auto_gas),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k, v, self.source(k)))
+        .collect()
+    }
+}
+
+fn override_cost(
+    c: &mut TxCost,
+    (max_gas, price, timeout): (Option<u64>, Option<u64>, Option<u64>),
+) {
+    if let Some(v) = max_gas {
+        c.max_gas_unit_for_tx = v;
+    }
+    if let Some(v) = price {
+        c.coin_price_per_unit = v;
+    }
+    if let Some(v) = timeout {
+        c.user_tx_timeout = v;
+    }
+}
+
+#[test]
+fn env_then_cli_layers() {
+    use crate::exports::AuthenticationKey;
+    use diem_temppath::TempPath;
+
+    let d = TempPath::new();
+    d.create_as_dir().unwrap();
+    let auth: AuthenticationKey =
+        "87515d94a244235a1433d7117bc0cb154c613c2f4b1e67ca8d98a542ee3f59f5"
+            .parse()
+            .unwrap();
+    let mut cfg = AppCfg::init_app_configs(
+        auth,
+        auth.derived_address(),
+        Some(d.path().to_owned()),
+        Some(NamedChain::TESTING),
+        None,
+    )
+    .unwrap();
+    cfg.tx_configs.baseline_cost.coin_price_per_unit = 7;
+    let file = cfg.save_file().unwrap();
+
+    let vars: BTreeMap<&str, &str> = [
+        (ENV_CONFIG_PATH, file.to_str().unwrap()),
+        (ENV_CHAIN_ID, "testnet"),
+        (
+            ENV_UPSTREAM_URLS,
+            "http://10.0.0.1:8080, http://10.0.0.2:8080",
+        ),
+        (ENV_MAX_GAS_UNIT_FOR_TX, "1234"),
+    ]
+    .into_iter()
+    .collect();
+    let env = ConfigOverrides::from_vars(|k| vars.get(k).map(|v| v.to_string())).unwrap();
+    let cli = ConfigOverrides {
+        upstream_urls: vec!["http://10.0.0.3:8080".parse().unwrap()],
+        ..Default::default()
+    };
+
+    let r = ResolvedCfg::load_layers(&env, &cli).unwrap();
+    assert_eq!(r.chain_id, NamedChain::TESTNET);
+    assert_eq!(r.source(KEY_CHAIN_ID), ValueSource::Env(ENV_CHAIN_ID));
+    // the cli flag wins over the env variable
+    let np = r
+        .cfg
+        .get_network_profile(Some(NamedChain::TESTNET))
+        .unwrap();
+    assert_eq!(np.nodes.len(), 1);
+    assert_eq!(r.source(KEY_UPSTREAM_URLS), ValueSource::Cli);
+    let cost = r.cfg.tx_configs.get_cost(None);
+    assert_eq!(cost.max_gas_unit_for_tx, 1234);
+    assert_eq!(cost.coin_price_per_unit, 7);
+    assert_eq!(r.source(KEY_GAS_PRICE), ValueSource::File(file));
+    assert_eq!(
+        r.source(KEY_MAX_GAS),
+        ValueSource::Env(ENV_MAX_GAS_UNIT_FOR_TX)
+    );
+
+    assert!(
+        ConfigOverrides::from_vars(|k| (k == ENV_USER_TX_TIMEOUT).then(|| "soon".to_owned()))
+            .is_err()
+    );
+
+    // a profile must be named exactly, not by a fragment of the address
+    let fragment = ConfigOverrides {
+        profile: Some("87515d94".to_owned()),
+        ..Default::default()
+    };
+    assert!(ResolvedCfg::load_layers(&env, &fragment).is_err());
+    let full = ConfigOverrides {
+        profile: Some(auth.derived_address().to_hex_literal()),
+        ..Default::default()
+    };
+    assert!(ResolvedCfg::load_layers(&env, &full).is_ok());
+}
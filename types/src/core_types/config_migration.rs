@@ -0,0 +1,322 @@
+//! Upgrades older config files to the current schema of libra-cli-config.yaml,
+//! and finds problems in a config file.
+//!
+//! Two kinds of files are upgraded:
+//! - the 0L.toml of v5 and v6, which had a single profile with its upstream
+//!   nodes, and a chain_info section.
+//! - a libra-cli-config.yaml without a config_version, where the profiles may
+//!   still have upstream_nodes instead of a network playlist.
+
+use super::{
+    app_cfg::{
+        get_nickname, AppCfg, LegacyChainId, LegacyToml, Profile, TxConfigs, Workspace,
+        CONFIG_VERSION,
+    },
+    network_playlist::{HostProfile, NetworkPlaylist},
+};
+use crate::exports::{AccountAddress, NamedChain};
+use anyhow::{bail, Context};
+use diem_types::chain_id::ChainId;
+use serde_yaml::Value;
+use std::{path::PathBuf, str::FromStr};
+use url::Url;
+
+/// A config upgraded to the current version, and what was changed.
+pub struct Migrated {
+    pub cfg: AppCfg,
+    pub notes: Vec<String>,
+}
+
+/// Upgrade a libra-cli-config.yaml to the current version.
+pub fn migrate_yaml(raw: &str) -> anyhow::Result<Migrated> {
+    let mut doc: Value = serde_yaml::from_str(raw).context("the file is not valid yaml")?;
+    let map = doc.as_mapping_mut().context("the file is not a yaml map")?;
+    let mut notes = vec![];
+
+    let version = map
+        .get(&key("config_version"))
+        .and_then(Value::as_u64)
+        .unwrap_or(0);
+    if version > CONFIG_VERSION {
+        bail!(
+            "the file has config version {}, newer than this version of the tools ({})",
+            version,
+            CONFIG_VERSION
+        );
+    }
+
+    let default_chain = map
+        .get(&key("workspace"))
+        .and_then(|w| w.get("default_chain_id"))
+        .and_then(Value::as_str)
+        .and_then(|c| NamedChain::from_str(c).ok())
+        .unwrap_or(NamedChain::MAINNET);
+
+    // the upstream nodes of the profiles become the network playlist
+    let mut upstream: Vec<Url> = vec![];
+    if let Some(Value::Sequence(profiles)) = map.get_mut(&key("user_profiles")) {
+        for p in profiles.iter_mut() {
+            let Some(p) = p.as_mapping_mut() else {
+                continue;
+            };
+            if let Some(Value::Sequence(nodes)) = p.remove(&key("upstream_nodes")) {
+                upstream.extend(nodes.iter().filter_map(|n| n.as_str()?.parse().ok()));
+            }
+            // older profiles had no nickname
+            if !p.contains_key(&key("nickname")) {
+                let account: AccountAddress = p
+                    .get(&key("account"))
+                    .and_then(Value::as_str)
+                    .context("a profile has no account")?
+                    .parse()?;
+                p.insert(key("nickname"), Value::from(get_nickname(account)));
+                notes.push(format!("added a nickname to the profile of {}", account));
+            }
+            if !p.contains_key(&key("statement")) {
+                p.insert(key("statement"), Value::from(Profile::default().statement));
+            }
+        }
+    }
+
+    let has_playlist = matches!(
+        map.get(&key("network_playlist")),
+        Some(Value::Sequence(s)) if !s.is_empty()
+    );
+    if !has_playlist {
+        let np = playlist_from_urls(upstream, default_chain);
+        notes.push(format!(
+            "made a network playlist for {} from the upstream nodes of the profiles",
+            np.chain_name
+        ));
+        map.insert(key("network_playlist"), serde_yaml::to_value(vec![np])?);
+    }
+
+    if !map.contains_key(&key("tx_configs")) {
+        map.insert(
+            key("tx_configs"),
+            serde_yaml::to_value(TxConfigs::default())?,
+        );
+        notes.push("added the default transaction costs".to_owned());
+    }
+
+    let mut cfg: AppCfg = serde_yaml::from_value(doc)
+        .context("the file could not be read as a config, even after the upgrade")?;
+    if cfg.config_version != CONFIG_VERSION {
+        notes.push(format!(
+            "config version {} to {}",
+            cfg.config_version, CONFIG_VERSION
+        ));
+        cfg.config_version = CONFIG_VERSION;
+    }
+    Ok(Migrated { cfg, notes })
+}
+
+/// Upgrade the 0L.toml of v5 and v6. The new file will be saved in node_home.
+pub fn migrate_legacy_toml(raw: &str, node_home: PathBuf) -> anyhow::Result<Migrated> {
+    let legacy: LegacyToml = toml::from_str(raw).context("could not read the 0L.toml")?;
+    let mut notes = vec![];
+
+    let account = AccountAddress::from_hex_literal(&format!(
+        "0x{}",
+        legacy.profile.account.trim_start_matches("0x")
+    ))?;
+
+    let chain = match legacy.chain_info.and_then(|c| c.chain_id) {
+        Some(LegacyChainId::Name(s)) => NamedChain::from_str(&s).ok(),
+        Some(LegacyChainId::Id(id)) => NamedChain::from_chain_id(&ChainId::new(id)).ok(),
+        None => None,
+    }
+    .unwrap_or_else(|| {
+        notes.push("no chain_id found, using MAINNET".to_owned());
+        NamedChain::MAINNET
+    });
+
+    let mut p = Profile::new(legacy.profile.auth_key, account);
+    if let Some(s) = legacy.profile.statement {
+        p.statement = s;
+    }
+    let upstream = legacy.profile.upstream_nodes;
+
+    // the transaction costs have the same fields
+    let tx_configs = legacy.tx_configs.unwrap_or_default();
+
+    notes.push(format!("kept the profile of {}", account));
+    notes.push(format!(
+        "kept {} upstream nodes in the network playlist",
+        upstream.len()
+    ));
+    let cfg = AppCfg {
+        config_version: CONFIG_VERSION,
+        workspace: Workspace {
+            default_profile: Some(p.nickname.clone()),
+            default_chain_id: chain,
+            node_home,
+        },
+        user_profiles: vec![p],
+        network_playlist: vec![playlist_from_urls(upstream, chain)],
+        tx_configs,
+    };
+    Ok(Migrated { cfg, notes })
+}
+
+fn playlist_from_urls(urls: Vec<Url>, chain: NamedChain) -> NetworkPlaylist {
+    let mut nodes: Vec<HostProfile> = vec![];
+    for u in urls {
+        if !nodes.iter().any(|n| n.url == u) {
+            nodes.push(HostProfile::new(u));
+        }
+    }
+    if nodes.is_empty() {
+        return NetworkPlaylist::localhost(Some(chain));
+    }
+    NetworkPlaylist {
+        chain_name: chain,
+        nodes,
+    }
+}
+
+/// The fields of a config file which are not part of the schema, and would be
+/// dropped on the next save. As paths, e.g. user_profiles[0].upstream_nodes
+pub fn unknown_fields(raw: &str) -> anyhow::Result<Vec<String>> {
+    let file: Value = serde_yaml::from_str(raw)?;
+    let cfg: AppCfg = serde_yaml::from_value(file.clone())?;
+    let known = serde_yaml::to_value(&cfg)?;
+    let mut list = vec![];
+    diff_keys(&file, &known, "", &mut list);
+    Ok(list)
+}
+
+fn diff_keys(file: &Value, known: &Value, path: &str, list: &mut Vec<String>) {
+    match (file, known) {
+        (Value::Mapping(f), Value::Mapping(k)) => {
+            for (name, v) in f {
+                let name = name.as_str().unwrap_or("?");
+                let p = if path.is_empty() {
+                    name.to_owned()
+                } else {
+                    format!("{}.{}", path, name)
+                };
+                match k.get(&key(name)) {
+                    Some(kv) => diff_keys(v, kv, &p, list),
+                    // optional fields which are empty are not saved
+                    None if v.is_null() => {}
+                    None => list.push(p),
+                }
+            }
+        }
+        (Value::Sequence(f), Value::Sequence(k)) => {
+            for (i, (fv, kv)) in f.iter().zip(k).enumerate() {
+                diff_keys(fv, kv, &format!("{}[{}]", path, i), list);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Profiles whose account is not derived from the auth key, nor its legacy
+/// (v5) form. These are either mistakes, or accounts which rotated their key.
+pub fn accounts_not_derived(cfg: &AppCfg) -> Vec<&Profile> {
+    cfg.user_profiles
+        .iter()
+        .filter(|p| {
+            let derived = p.auth_key.derived_address();
+            // legacy accounts are the last 16 bytes of the derived address
+            if p.account[..16] == [0u8; 16] {
+                p.account[16..] != derived[16..]
+            } else {
+                p.account != derived
+            }
+        })
+        .collect()
+}
+
+fn key(s: &str) -> Value {
+    Value::String(s.to_owned())
+}
+
+#[test]
+fn migrate_unversioned_yaml() {
+    let raw = r"
+workspace:
+  default_profile: '636'
+  default_chain_id: TESTING
+  node_home: /tmp/.libra
+user_profiles:
+- account: 63609dfa4c8786bef29b201500064b2864689de724ca134f4e975784e3642776
+  auth_key: 0x63609dfa4c8786bef29b201500064b2864689de724ca134f4e975784e3642776
+  test_private_key: null
+  locale: null
+  statement: Protests rage across the nation
+  upstream_nodes:
+  - http://10.0.0.1:8080/
+  - http://10.0.0.2:8080/
+";
+    let m = migrate_yaml(raw).unwrap();
+    assert_eq!(m.cfg.config_version, CONFIG_VERSION);
+    assert_eq!(m.cfg.user_profiles[0].nickname, "6360");
+    let np = m.cfg.get_network_profile(None).unwrap();
+    assert_eq!(np.chain_name, NamedChain::TESTING);
+    assert_eq!(np.nodes.len(), 2);
+    assert!(accounts_not_derived(&m.cfg).is_empty());
+
+    // nothing left to upgrade
+    let again = migrate_yaml(&serde_yaml::to_string(&m.cfg).unwrap()).unwrap();
+    assert!(again.notes.is_empty());
+    assert!(unknown_fields(&serde_yaml::to_string(&m.cfg).unwrap())
+        .unwrap()
+        .is_empty());
+
+    let mut extra = serde_yaml::to_value(&m.cfg).unwrap();
+    extra["user_profiles"][0]
+        .as_mapping_mut()
+        .unwrap()
+        .insert(key("upstream_nodes"), Value::from("http://10.0.0.1:8080/"));
+    assert_eq!(
+        unknown_fields(&serde_yaml::to_string(&extra).unwrap()).unwrap(),
+        vec!["user_profiles[0].upstream_nodes".to_owned()]
+    );
+}
+
+#[test]
+fn migrate_0l_toml() {
+    let raw = r#"
+[workspace]
+node_home = "/root/.0L"
+block_dir = "vdf_proofs"
+
+[profile]
+account = "4c613c2f4b1e67ca8d98a542ee3f59f5"
+auth_key = "87515d94a244235a1433d7117bc0cb154c613c2f4b1e67ca8d98a542ee3f59f5"
+statement = "alice"
+ip = "0.0.0.0"
+upstream_nodes = ["http://1.2.3.4:8080/"]
+
+[chain_info]
+chain_id = "TESTING"
+
+[tx_configs.baseline_cost]
+max_gas_unit_for_tx = 10000
+coin_price_per_unit = 1
+user_tx_timeout = 5000
+"#;
+    let m = migrate_legacy_toml(raw, PathBuf::from("/tmp/.libra")).unwrap();
+    let p = m.cfg.get_profile(None).unwrap();
+    assert_eq!(p.statement, "alice");
+    // a v5 address is kept as it is
+    assert_eq!(
+        p.account.to_string(),
+        "000000000000000000000000000000004c613c2f4b1e67ca8d98a542ee3f59f5"
+    );
+    assert!(accounts_not_derived(&m.cfg).is_empty());
+    assert_eq!(m.cfg.workspace.default_chain_id, NamedChain::TESTING);
+    assert_eq!(
+        m.cfg.network_playlist[0].nodes[0].url.as_str(),
+        "http://1.2.3.4:8080/"
+    );
+    assert_eq!(m.cfg.tx_configs.baseline_cost.max_gas_unit_for_tx, 10000);
+
+    // older files have the chain id as a number
+    let raw = raw.replace(r#"chain_id = "TESTING""#, "chain_id = 4");
+    let m = migrate_legacy_toml(&raw, PathBuf::from("/tmp/.libra")).unwrap();
+    assert_eq!(m.cfg.workspace.default_chain_id, NamedChain::TESTING);
+}
@@ -0,0 +1,97 @@
+//! Explains Move abort codes with the error constants of the framework
+//! modules. The error maps are read from the release bundles by
+//! libra-framework, see `libra_framework::error_map`.
+
+use move_core_types::errmap::ErrorDescription;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// module id (e.g. 0x1::slow_wallet) => abort reason => error constant
+pub type ErrorMaps = BTreeMap<String, BTreeMap<u64, ErrorDescription>>;
+
+/// A Move abort code, explained.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AbortExplanation {
+    /// the std::error category, e.g. INVALID_ARGUMENT
+    pub category: Option<String>,
+    /// the error constant, e.g. EGENESIS_ERROR
+    pub reason_name: String,
+    /// the doc comment of the error constant
+    pub description: String,
+}
+
+/// The std::error categories, which are the upper bits of an abort code.
+pub fn error_category(code: u64) -> Option<&'static str> {
+    let name = match code >> 16 {
+        0x1 => "INVALID_ARGUMENT",
+        0x2 => "OUT_OF_RANGE",
+        0x3 => "INVALID_STATE",
+        0x4 => "UNAUTHENTICATED",
+        0x5 => "PERMISSION_DENIED",
+        0x6 => "NOT_FOUND",
+        0x7 => "ABORTED",
+        0x8 => "ALREADY_EXISTS",
+        0x9 => "RESOURCE_EXHAUSTED",
+        0xA => "CANCELLED",
+        0xB => "INTERNAL",
+        0xC => "NOT_IMPLEMENTED",
+        0xD => "UNAVAILABLE",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Look up an abort code of a module, e.g. ("0x1::slow_wallet", 65537).
+pub fn explain_abort(maps: &ErrorMaps, module_id: &str, code: u64) -> Option<AbortExplanation> {
+    // the lower bits are the error constant
+    let reason = code & 0xFFFF;
+    let desc = maps.get(module_id)?.get(&reason)?;
+    Some(AbortExplanation {
+        category: error_category(code).map(str::to_owned),
+        reason_name: desc.code_name.to_owned(),
+        description: desc.code_description.to_owned(),
+    })
+}
+
+/// Explain the vm_status string of a transaction from the REST API, e.g.
+/// "Move abort in 0x1::slow_wallet: 0x10001".
+pub fn explain_vm_status(maps: &ErrorMaps, vm_status: &str) -> Option<AbortExplanation> {
+    let rest = vm_status.strip_prefix("Move abort in ")?;
+    let (module_id, code) = rest.split_once(": ")?;
+    // when the node already knew the error it is NAME(0x..): description
+    let code = match code.split_once('(') {
+        Some((_, c)) => c.split(')').next()?,
+        None => code,
+    };
+    let code = u64::from_str_radix(code.trim().trim_start_matches("0x"), 16).ok()?;
+    explain_abort(maps, module_id, code)
+}
+
+#[test]
+fn explain_aborts() {
+    let mut maps = ErrorMaps::new();
+    maps.entry("0x1::slow_wallet".to_owned())
+        .or_default()
+        .insert(
+            1,
+            ErrorDescription {
+                code_name: "EGENESIS_ERROR".to_owned(),
+                code_description: "genesis error".to_owned(),
+            },
+        );
+
+    let e = explain_abort(&maps, "0x1::slow_wallet", 0x10001).unwrap();
+    assert_eq!(e.reason_name, "EGENESIS_ERROR");
+    assert_eq!(e.category.as_deref(), Some("INVALID_ARGUMENT"));
+
+    let from_api = explain_vm_status(&maps, "Move abort in 0x1::slow_wallet: 0x10001");
+    assert_eq!(from_api.as_ref(), Some(&e));
+    let known = explain_vm_status(
+        &maps,
+        "Move abort in 0x1::slow_wallet: EGENESIS_ERROR(0x10001): genesis error",
+    );
+    assert_eq!(known, Some(e));
+
+    assert!(explain_abort(&maps, "0x1::not_a_module", 1).is_none());
+    assert!(explain_vm_status(&maps, "Executed successfully").is_none());
+}
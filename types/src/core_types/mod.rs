@@ -1,7 +1,12 @@
 pub mod app_cfg;
 pub mod block;
+pub mod cli_error;
+pub mod config_layers;
+pub mod config_migration;
+pub mod error_map;
 pub mod fixtures;
 pub mod legacy_currency_info;
 pub mod mode_ol;
 pub mod network_playlist;
 pub mod pledge;
+pub mod proposal_metadata;
@@ -4,6 +4,7 @@ use anyhow::{bail, Context};
 use futures::{stream::FuturesUnordered, StreamExt};
 use rand::{seq::SliceRandom, thread_rng};
 use serde_with::{serde_as, DisplayFromStr};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 use url::Url;
 
 #[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
@@ -16,6 +17,15 @@ pub struct HostProfile {
     pub is_api: bool,
     #[serde(default)]
     pub is_sync: bool,
+    /// the ledger timestamp (microseconds) at the last check
+    #[serde(default)]
+    pub ledger_timestamp: u64,
+    /// unix time (seconds) of the last health check
+    #[serde(default)]
+    pub last_checked: u64,
+    /// response time of the API (milliseconds) at the last health check
+    #[serde(default)]
+    pub latency_ms: u64,
 }
 /// from the list of seed_peers find the best peer to connect to.
 /// First does a light port check on all peers, and eliminated unresponsive
@@ -30,6 +40,9 @@ impl Default for HostProfile {
             version: 0,
             is_api: false,
             is_sync: false,
+            ledger_timestamp: 0,
+            last_checked: 0,
+            latency_ms: 0,
             note: "default".to_string(),
         }
     }
@@ -46,13 +59,19 @@ impl HostProfile {
     }
 
     /// Checks the sync status of the host.
-    /// Attempts to fetch the ledger version from the host.
-    async fn check_sync(mut self) -> anyhow::Result<HostProfile> {
+    /// Attempts to fetch the ledger version and timestamp from the host,
+    /// and records when it was checked and how long the API took to respond.
+    pub async fn check_sync(mut self) -> anyhow::Result<HostProfile> {
         let client = Client::new(self.url.clone());
 
+        self.last_checked = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let start = Instant::now();
         match client.get_index().await {
             Ok(res) => {
-                self.version = res.into_inner().ledger_version.into();
+                self.latency_ms = start.elapsed().as_millis() as u64;
+                let index = res.into_inner();
+                self.version = index.ledger_version.into();
+                self.ledger_timestamp = index.ledger_timestamp.into();
                 self.is_api = true;
             }
             Err(_) => {
@@ -122,6 +141,9 @@ impl NetworkPlaylist {
                 version: 0,
                 is_api: true,
                 is_sync: true,
+                ledger_timestamp: 0,
+                last_checked: 0,
+                latency_ms: 0,
             }],
         }
     }
@@ -196,15 +218,23 @@ impl NetworkPlaylist {
         }
     }
 
+    /// Picks the healthiest node from the last check: a synced node if there
+    /// is one, otherwise any node whose API responded, otherwise the first url.
     pub fn pick_one(&self) -> anyhow::Result<Url> {
-        match self.the_best_one() {
-            Ok(u) => Ok(u),
-            Err(_) => self
-                .all_urls()?
-                .into_iter()
-                .next()
-                .context("no urls to choose from"),
+        // the file may have been edited by hand since the last ranking
+        let mut ranked = self.clone();
+        ranked.rank_nodes();
+
+        if let Ok(u) = ranked.the_best_one() {
+            return Ok(u);
         }
+        if let Some(h) = ranked.nodes.iter().find(|e| e.is_api) {
+            return Ok(h.url.to_owned());
+        }
+        self.all_urls()?
+            .into_iter()
+            .next()
+            .context("no urls to choose from")
     }
 
     pub async fn refresh_sync_status(&mut self) -> anyhow::Result<()> {
@@ -224,34 +254,44 @@ impl NetworkPlaylist {
             .collect::<Vec<HostProfile>>()
             .await;
 
-        // find the RMS of the versions. Reject anything below rms
-        let sum_squares: u64 = sync_list
+        // find the RMS of the versions of the nodes which responded.
+        // Reject anything below rms
+        let api_versions: Vec<f64> = sync_list
             .iter()
-            .map(|e| u64::pow(e.version, 2))
-            .collect::<Vec<u64>>()
-            .iter()
-            .sum();
+            .filter(|e| e.is_api)
+            .map(|e| e.version as f64)
+            .collect();
+        let sum_squares: f64 = api_versions.iter().map(|v| v.powi(2)).sum();
 
-        let avg = sum_squares as f64 / sync_list.len() as f64;
+        let avg = sum_squares / api_versions.len() as f64;
         let rms = avg.sqrt();
 
-        let mut checked: Vec<HostProfile> = sync_list
+        self.nodes = sync_list
             .into_iter()
             .map(|mut p| {
-                if p.version as f64 >= rms {
-                    // there may be only one in list
-                    p.is_sync = true
-                }
+                // there may be only one in list
+                p.is_sync = p.is_api && p.version as f64 >= rms;
                 p
             })
             .collect();
 
-        checked.sort_by_key(|p| p.version);
-        self.nodes = checked;
+        self.rank_nodes();
 
         Ok(())
     }
 
+    /// Sorts the nodes healthiest first: synced nodes, then responsive ones,
+    /// by highest version and then lowest latency.
+    pub fn rank_nodes(&mut self) {
+        self.nodes.sort_by(|a, b| {
+            b.is_sync
+                .cmp(&a.is_sync)
+                .then(b.is_api.cmp(&a.is_api))
+                .then(b.version.cmp(&a.version))
+                .then(a.latency_ms.cmp(&b.latency_ms))
+        });
+    }
+
     /// Checks which nodes are alive by performing a sync check.
     pub async fn check_which_are_alive(mut self) -> anyhow::Result<Self> {
         let mut upstream = self.nodes;
@@ -277,3 +317,44 @@ impl NetworkPlaylist {
         Ok(self)
     }
 }
+
+#[test]
+fn pick_the_healthiest_node() {
+    let mut np = NetworkPlaylist::new(Some("http://dead.node".parse().unwrap()), None);
+
+    let mut slow = HostProfile::new("http://slow.node".parse().unwrap());
+    slow.is_api = true;
+    slow.is_sync = true;
+    slow.version = 100;
+    slow.latency_ms = 900;
+    np.nodes.push(slow);
+
+    let mut fast = HostProfile::new("http://fast.node".parse().unwrap());
+    fast.is_api = true;
+    fast.is_sync = true;
+    fast.version = 100;
+    fast.latency_ms = 30;
+    np.nodes.push(fast);
+
+    let mut behind = HostProfile::new("http://behind.node".parse().unwrap());
+    behind.is_api = true;
+    behind.version = 10;
+    np.nodes.push(behind);
+
+    np.rank_nodes();
+    let order: Vec<String> = np
+        .nodes
+        .iter()
+        .map(|e| e.url.host_str().unwrap().to_owned())
+        .collect();
+    assert_eq!(
+        order,
+        vec!["fast.node", "slow.node", "behind.node", "dead.node"]
+    );
+    assert_eq!(np.pick_one().unwrap().host_str(), Some("fast.node"));
+
+    // with no synced nodes, prefer one which at least responded
+    np.nodes.iter_mut().for_each(|e| e.is_sync = false);
+    np.nodes.reverse();
+    assert_eq!(np.pick_one().unwrap().host_str(), Some("behind.node"));
+}
@@ -0,0 +1,141 @@
+//! The metadata document of a governance proposal.
+//!
+//! The document is published by the proposer, and only its location and
+//! hash go on chain. The hash is the sha3 of the bytes of the file, so that
+//! anyone can check a copy of it, e.g. with `sha3sum -a 256`.
+
+use anyhow::{bail, Context};
+use diem_crypto::HashValue;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// File name of the document, in the directory of the upgrade scripts
+pub const PROPOSAL_METADATA_FILE: &str = "proposal_metadata.json";
+
+/// What a proposal does, for the voters
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProposalMetadata {
+    pub title: String,
+    pub description: String,
+    /// commit of the framework source which was compiled
+    pub framework_git_hash: String,
+    pub release_name: String,
+    /// the scripts of the upgrade, in the order they are resolved
+    pub steps: Vec<ScriptStep>,
+    pub changelog_url: Option<String>,
+}
+
+/// One script of a multi-step upgrade
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptStep {
+    /// directory name of the script, e.g. 1-move-stdlib
+    pub name: String,
+    /// hex of the sha3 of the compiled script, as in its script_sha3 file
+    pub script_hash: String,
+}
+
+impl ProposalMetadata {
+    /// save the document to the directory, returns the path of the file
+    pub fn save(&self, dir: &Path) -> anyhow::Result<PathBuf> {
+        let path = dir.join(PROPOSAL_METADATA_FILE);
+        fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+
+    /// parse a document, and the hash of its bytes
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<(Self, HashValue)> {
+        let meta = serde_json::from_slice(bytes).context("not a proposal metadata document")?;
+        Ok((meta, HashValue::sha3_256_of(bytes)))
+    }
+
+    /// read a document, and the hash of the file
+    pub fn read(path: &Path) -> anyhow::Result<(Self, HashValue)> {
+        let bytes = fs::read(path).context(format!(
+            "could not read the proposal metadata at {}",
+            path.display()
+        ))?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// find the document of a script dir, in the dir or in the dir of all the steps
+    pub fn find(script_dir: &Path) -> Option<PathBuf> {
+        [Some(script_dir), script_dir.parent()]
+            .into_iter()
+            .flatten()
+            .map(|d| d.join(PROPOSAL_METADATA_FILE))
+            .find(|p| p.exists())
+    }
+
+    /// download the document from where the proposal says it is published
+    pub async fn fetch(url: &str) -> anyhow::Result<(Self, HashValue)> {
+        let res = reqwest::get(url)
+            .await
+            .context(format!(
+                "could not download the proposal metadata at {}",
+                url
+            ))?
+            .error_for_status()?;
+        Self::from_bytes(&res.bytes().await?)
+    }
+
+    /// the hash of the document must be the one on chain
+    pub fn check_hash(hash: &HashValue, onchain_hash: &str) -> anyhow::Result<()> {
+        let onchain = onchain_hash.trim().trim_start_matches("0x");
+        if !hash.to_hex().eq_ignore_ascii_case(onchain) {
+            bail!(
+                "the proposal metadata has hash {}, but the proposal on chain has {}",
+                hash.to_hex(),
+                onchain
+            );
+        }
+        Ok(())
+    }
+
+    /// print the document for a voter
+    pub fn display(&self) -> String {
+        let mut s = format!(
+            "{}\n{}\nrelease: {}, framework commit: {}\n",
+            self.title, self.description, self.release_name, self.framework_git_hash
+        );
+        for step in &self.steps {
+            s.push_str(&format!(
+                "step {}: script hash {}\n",
+                step.name, step.script_hash
+            ));
+        }
+        if let Some(c) = &self.changelog_url {
+            s.push_str(&format!("changelog: {}\n", c));
+        }
+        s
+    }
+}
+
+#[test]
+fn metadata_hash_of_file() {
+    use diem_temppath::TempPath;
+
+    let d = TempPath::new();
+    d.create_as_dir().unwrap();
+    let meta = ProposalMetadata {
+        title: "v7.0.1".to_owned(),
+        release_name: "v7.0.1".to_owned(),
+        steps: vec![ScriptStep {
+            name: "1-move-stdlib".to_owned(),
+            script_hash: "ab".repeat(32),
+        }],
+        ..Default::default()
+    };
+    let path = meta.save(d.path()).unwrap();
+    let step_dir = d.path().join("1-move-stdlib");
+    fs::create_dir_all(&step_dir).unwrap();
+    assert_eq!(ProposalMetadata::find(&step_dir).unwrap(), path);
+
+    let (read, hash) = ProposalMetadata::read(&path).unwrap();
+    assert_eq!(read, meta);
+    assert_eq!(hash, HashValue::sha3_256_of(&fs::read(&path).unwrap()));
+    ProposalMetadata::check_hash(&hash, &hash.to_hex_literal()).unwrap();
+    assert!(ProposalMetadata::check_hash(&hash, "metadata struct").is_err());
+}
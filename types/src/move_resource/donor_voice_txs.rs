@@ -6,12 +6,14 @@ use move_core_types::{
 };
 use serde::{Deserialize, Serialize};
 
+use crate::move_resource::gas_coin::cast_coin_to_decimal;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TxScheduleResource {
-    scheduled: Vec<TimedTransferResource>,
-    veto: Vec<TimedTransferResource>,
-    paid: Vec<TimedTransferResource>,
-    guid_capability: GUIDCapabilityResource, // we need this for the MultiSig
+    pub scheduled: Vec<TimedTransferResource>,
+    pub veto: Vec<TimedTransferResource>,
+    pub paid: Vec<TimedTransferResource>,
+    pub guid_capability: GUIDCapabilityResource, // we need this for the MultiSig
 }
 
 impl MoveStructType for TxScheduleResource {
@@ -22,7 +24,7 @@ impl MoveStructType for TxScheduleResource {
 impl MoveResource for TxScheduleResource {}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
-struct IDResource {
+pub struct IDResource {
     /// If creation_num is `i`, this is the `i+1`th GUID created by `addr`
     pub creation_num: u64,
     /// Address that created the GUID
@@ -38,10 +40,10 @@ impl MoveResource for IDResource {}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TimedTransferResource {
-    uid: IDResource,     // copy of ID generated by MultiSig for the transaction
-    deadline: u64,       // what epoch does the transaction execute
-    tx: PaymentResource, // The transaction properties
-    epoch_latest_veto_received: u64, // This is to check if we need to extend the deadline
+    pub uid: IDResource,     // copy of ID generated by MultiSig for the transaction
+    pub deadline: u64,       // what epoch does the transaction execute
+    pub tx: PaymentResource, // The transaction properties
+    pub epoch_latest_veto_received: u64, // This is to check if we need to extend the deadline
 }
 
 impl MoveStructType for TimedTransferResource {
@@ -53,9 +55,9 @@ impl MoveResource for TimedTransferResource {}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PaymentResource {
-    payee: AccountAddress,
-    value: u64,
-    description: Vec<u8>,
+    pub payee: AccountAddress,
+    pub value: u64,
+    pub description: Vec<u8>,
 }
 
 impl MoveStructType for PaymentResource {
@@ -67,7 +69,7 @@ impl MoveResource for PaymentResource {}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GUIDCapabilityResource {
-    addr: AccountAddress,
+    pub addr: AccountAddress,
 }
 impl MoveStructType for GUIDCapabilityResource {
     const MODULE_NAME: &'static IdentStr = ident_str!("account");
@@ -75,3 +77,82 @@ impl MoveStructType for GUIDCapabilityResource {
 }
 
 impl MoveResource for GUIDCapabilityResource {}
+
+impl TimedTransferResource {
+    /// scale the coin value and decode the description for human consumption
+    pub fn to_display(&self, veto_tally: Option<VetoTally>) -> TimedTransferDisplay {
+        TimedTransferDisplay {
+            uid: self.uid.creation_num,
+            payee: self.tx.payee,
+            amount: cast_coin_to_decimal(self.tx.value),
+            description: String::from_utf8_lossy(&self.tx.description).to_string(),
+            deadline_epoch: self.deadline,
+            epoch_latest_veto_received: self.epoch_latest_veto_received,
+            veto_tally,
+        }
+    }
+}
+
+/// The tally of a veto vote by the donors, as returned by donor_voice_governance::get_veto_tally.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VetoTally {
+    /// percent of the donor turnout which voted to veto
+    pub approval_pct: u64,
+    /// the threshold required for the veto to pass, adjusted for turnout
+    pub threshold: u64,
+}
+
+/// A TimedTransfer with the coin value scaled and the description decoded.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TimedTransferDisplay {
+    /// the guid creation number, used to vote on a veto
+    pub uid: u64,
+    pub payee: AccountAddress,
+    pub amount: f64,
+    pub description: String,
+    pub deadline_epoch: u64,
+    pub epoch_latest_veto_received: u64,
+    /// only present if the donors have started a veto vote
+    pub veto_tally: Option<VetoTally>,
+}
+
+/// The scheduled, vetoed, and paid transfers of a Donor Voice account.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TxScheduleDisplay {
+    pub scheduled: Vec<TimedTransferDisplay>,
+    pub veto: Vec<TimedTransferDisplay>,
+    pub paid: Vec<TimedTransferDisplay>,
+}
+
+/// The multi_action authorities of a Donor Voice account, and the n-of-m threshold.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MultiAuthSigners {
+    pub authorities: Vec<AccountAddress>,
+    /// signatures required to approve a transaction
+    pub n_required: u64,
+    /// total number of authorities
+    pub m_total: u64,
+}
+
+#[test]
+fn timed_transfer_display() {
+    let t = TimedTransferResource {
+        uid: IDResource {
+            creation_num: 3,
+            addr: AccountAddress::ONE,
+        },
+        deadline: 10,
+        tx: PaymentResource {
+            payee: AccountAddress::TWO,
+            value: 1_500_000,
+            description: "thanks for all the fish".as_bytes().to_vec(),
+        },
+        epoch_latest_veto_received: 0,
+    };
+
+    let d = t.to_display(None);
+    assert_eq!(d.uid, 3);
+    assert_eq!(d.amount, 1.5);
+    assert_eq!(d.description, "thanks for all the fish");
+    assert_eq!(d.deadline_epoch, 10);
+}
@@ -6,6 +6,7 @@ use crate::{
 use anyhow::{anyhow, Context};
 use async_trait::async_trait;
 use diem::common::types::{CliConfig, ConfigSearchMode, DEFAULT_PROFILE};
+use diem_logger::prelude::warn;
 use diem_sdk::{
     move_types::{
         language_storage::{ModuleId, TypeTag},
@@ -25,12 +26,14 @@ use diem_sdk::{
         LocalAccount,
     },
 };
+use futures::{stream::FuturesUnordered, StreamExt};
 use serde::de::DeserializeOwned;
 use serde_json::{self, Value};
 use std::{
     str::FromStr,
-    time::{SystemTime, UNIX_EPOCH},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
+use tokio::time::timeout;
 use url::Url;
 
 pub const DEFAULT_TIMEOUT_SECS: u64 = 10;
@@ -45,7 +48,10 @@ pub trait ClientExt {
         chain_id_opt: Option<NamedChain>,
     ) -> anyhow::Result<(Client, ChainId)>;
 
-    async fn find_good_upstream(list: Vec<Url>) -> anyhow::Result<(Client, ChainId)>;
+    async fn find_good_upstream(
+        list: Vec<Url>,
+        chain_id_opt: Option<ChainId>,
+    ) -> anyhow::Result<(Client, ChainId)>;
 
     fn from_vendor_config() -> anyhow::Result<Client>;
 
@@ -89,32 +95,75 @@ impl ClientExt for Client {
         Ok(client)
     }
 
-    /// Finds a good working upstream based on the list in a config file
+    /// Finds a good working upstream based on the list in a config file.
+    /// Uses the preferred node of the playlist, and if it is down, falls back
+    /// to the most synced node of the playlist on the same chain.
     async fn from_libra_config(
         app_cfg: &AppCfg,
         chain_id_opt: Option<NamedChain>,
     ) -> anyhow::Result<(Client, ChainId)> {
-        // check if we can connect to this client, or exit
-        let url = &app_cfg.pick_url(chain_id_opt)?;
-        let client = Client::new(url.to_owned());
-        let res = client.get_index().await?;
+        let np = app_cfg.get_network_profile(chain_id_opt)?;
+        let url = np.pick_one()?;
+        let client = Client::new(url.clone());
+        // check if we can connect to this client
+        if let Ok(Ok(res)) = timeout(
+            Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+            client.get_index(),
+        )
+        .await
+        {
+            return Ok((client, ChainId::new(res.inner().chain_id)));
+        }
 
-        Ok((client, ChainId::new(res.inner().chain_id)))
+        warn!(
+            "cannot connect to {}, looking for another upstream node",
+            url
+        );
+        Self::find_good_upstream(np.all_urls()?, Some(ChainId::new(np.chain_name.id())))
+            .await
+            .context(format!(
+                "no upstream node available for chain {}",
+                np.chain_name
+            ))
     }
 
-    async fn find_good_upstream(_list: Vec<Url>) -> anyhow::Result<(Client, ChainId)> {
-        // TODO: iterate through all and find a valid one.
-
-        //   let metadata =  future::select_all(
-        //     nodes.into_iter().find_map(|u| async {
-        //         let client = Client::new(u);
-        //         match client.get_index().await {
-        //             Ok(index) => Some((client, index.inner().chain_id)),
-        //             _ => None,
-        //         }
-        //     })
-        // ).await?;
-        todo!()
+    /// Probes all the urls concurrently, drops the unresponsive ones and the ones
+    /// on a different chain, and picks the one with the highest ledger version.
+    async fn find_good_upstream(
+        list: Vec<Url>,
+        chain_id_opt: Option<ChainId>,
+    ) -> anyhow::Result<(Client, ChainId)> {
+        let futures = FuturesUnordered::new();
+        list.into_iter().for_each(|u| {
+            futures.push(async move {
+                let client = Client::new(u);
+                match timeout(
+                    Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+                    client.get_index(),
+                )
+                .await
+                {
+                    Ok(Ok(res)) => {
+                        let index = res.into_inner();
+                        let version: u64 = index.ledger_version.into();
+                        Some((client, ChainId::new(index.chain_id), version))
+                    }
+                    _ => None,
+                }
+            });
+        });
+
+        let responsive = futures
+            .filter_map(|e| async move { e })
+            .collect::<Vec<(Client, ChainId, u64)>>()
+            .await;
+
+        responsive
+            .into_iter()
+            .filter(|(_, chain_id, _)| chain_id_opt.map_or(true, |c| c == *chain_id))
+            .max_by_key(|(_, _, version)| *version)
+            .map(|(client, chain_id, _)| (client, chain_id))
+            .context("could not find a responsive upstream node")
     }
 
     fn from_vendor_config() -> anyhow::Result<Client> {
@@ -300,3 +349,10 @@ struct Person {
     x: f64,
     y: f64,
 }
+
+#[tokio::test]
+async fn find_good_upstream_drops_dead_nodes() {
+    // nothing listens on port 1
+    let list = vec!["http://127.0.0.1:1".parse().unwrap()];
+    assert!(Client::find_good_upstream(list, None).await.is_err());
+}
@@ -44,6 +44,16 @@ async fn workflow_upgrade_head_multiple_steps() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// same as above, but all the steps are resolved with `txs governance resolve-all`
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn workflow_upgrade_head_resolve_all() -> anyhow::Result<()> {
+    let mut s = LibraSmoke::new_with_target(Some(1), None, ReleaseTarget::Head)
+        .await
+        .context("could not start libra smoke")?;
+    support::upgrade_resolve_all_impl(&mut s, "upgrade-multi-lib").await?;
+    Ok(())
+}
+
 /// do the same as above, but use the "arbitrary" upgrade policy to force an
 /// upgrade.
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
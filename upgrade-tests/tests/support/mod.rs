@@ -1,3 +1,4 @@
+#![allow(dead_code)]
 use anyhow::Context;
 use diem_types::chain_id::NamedChain;
 use libra_framework::upgrade_fixtures;
@@ -5,7 +6,7 @@ use libra_query::query_view;
 use libra_smoke_tests::{configure_validator, libra_smoke::LibraSmoke};
 use libra_txs::{
     txs_cli::{TxsCli, TxsSub::Governance},
-    txs_cli_governance::GovernanceTxs::{Propose, Resolve, Vote},
+    txs_cli_governance::GovernanceTxs::{Propose, Resolve, ResolveAll, Vote},
 };
 use libra_types::core_types::app_cfg::TxCost;
 
@@ -20,9 +21,52 @@ pub async fn upgrade_multiple_impl(
     dir_path: &str,
     modules: Vec<&str>,
 ) -> anyhow::Result<()> {
-    upgrade_fixtures::testsuite_maybe_warmup_fixtures();
+    let d = diem_temppath::TempPath::new();
+    let (mut cli, prop_id) = propose_and_vote(s, &d, dir_path, modules[0]).await?;
+
+    //////////// RESOLVE ////////////
+
+    for name in modules {
+        ///////// SHOWTIME, RESOLVE EACH STEP ////////
+
+        let script_dir = upgrade_fixtures::fixtures_path().join(dir_path).join(name);
 
+        cli.subcommand = Some(Governance(Resolve {
+            proposal_id: prop_id,
+            proposal_script_dir: script_dir,
+        }));
+        cli.run()
+            .await
+            .map_err(|e| e.context(format!("cannot resolve proposal at step {name}")))?;
+    }
+
+    check_upgraded(s).await
+}
+
+/// Same as above, but all the steps in the fixtures dir are resolved with one
+/// `txs governance resolve-all`.
+pub async fn upgrade_resolve_all_impl(s: &mut LibraSmoke, dir_path: &str) -> anyhow::Result<()> {
     let d = diem_temppath::TempPath::new();
+    let (mut cli, prop_id) = propose_and_vote(s, &d, dir_path, "1-move-stdlib").await?;
+
+    cli.subcommand = Some(Governance(ResolveAll {
+        proposal_id: prop_id,
+        upgrade_dir: upgrade_fixtures::fixtures_path().join(dir_path),
+    }));
+    cli.run().await.context("cannot resolve all the steps")?;
+
+    check_upgraded(s).await
+}
+
+/// Propose the upgrade with its first step, and vote for it until it can be
+/// resolved. The config files of the validator are written in `d`.
+async fn propose_and_vote(
+    s: &mut LibraSmoke,
+    d: &diem_temppath::TempPath,
+    dir_path: &str,
+    first_step: &str,
+) -> anyhow::Result<(TxsCli, u64)> {
+    upgrade_fixtures::testsuite_maybe_warmup_fixtures();
 
     let (_, _app_cfg) =
         configure_validator::init_val_config_files(&mut s.swarm, 0, Some(d.path().to_owned()))
@@ -45,15 +89,18 @@ pub async fn upgrade_multiple_impl(
     // Set up governance proposal, just with first module
     let script_dir = upgrade_fixtures::fixtures_path()
         .join(dir_path)
-        .join(modules[0]); // take first module usually "1-move-stdlib"
+        .join(first_step); // usually "1-move-stdlib"
     assert!(script_dir.exists(), "can't find upgrade fixtures");
 
     let mut cli = TxsCli {
         subcommand: Some(Governance(Propose {
             proposal_script_dir: script_dir.clone(),
             metadata_url: "http://allyourbase.com".to_string(),
+            metadata_file: None,
         })),
         mnemonic: None,
+        passphrase: false,
+        profile: None,
         test_private_key: Some(s.encoded_pri_key.clone()),
         chain_id: Some(NamedChain::TESTING),
         config_path: Some(d.path().to_owned().join("libra-cli-config.yaml")),
@@ -61,6 +108,7 @@ pub async fn upgrade_multiple_impl(
         tx_profile: None,
         tx_cost: Some(TxCost::framework_upgrade()),
         estimate_only: false,
+        auto_gas: false,
         legacy_address: false,
     };
 
@@ -91,6 +139,7 @@ pub async fn upgrade_multiple_impl(
     cli.subcommand = Some(Governance(Vote {
         proposal_id: prop_id,
         should_fail: false,
+        skip_metadata_check: false,
     }));
     cli.run().await.context("alice votes on prop")?;
 
@@ -147,22 +196,10 @@ pub async fn upgrade_multiple_impl(
         "expected this script hash, did you change the fixtures?"
     );
 
-    //////////// RESOLVE ////////////
-
-    for name in modules {
-        ///////// SHOWTIME, RESOLVE EACH STEP ////////
-
-        let script_dir = upgrade_fixtures::fixtures_path().join(dir_path).join(name);
-
-        cli.subcommand = Some(Governance(Resolve {
-            proposal_id: prop_id,
-            proposal_script_dir: script_dir,
-        }));
-        cli.run()
-            .await
-            .map_err(|e| e.context(format!("cannot resolve proposal at step {name}")))?;
-    }
+    Ok((cli, prop_id))
+}
 
+async fn check_upgraded(s: &LibraSmoke) -> anyhow::Result<()> {
     //////////// VERIFY SUCCESS ////////////
     let query_res =
         query_view::get_view(&s.client(), "0x1::all_your_base::are_belong_to", None, None)